@@ -0,0 +1,51 @@
+use oxrdf::Dataset;
+use oxttl::NQuadsParser;
+use pyo3::{create_exception, exceptions::PyValueError, prelude::*};
+use rdf_canon::CanonicalizationError;
+use std::collections::HashMap;
+
+create_exception!(
+    rdf_canon,
+    CanonicalizationException,
+    PyValueError,
+    "Raised when the input cannot be parsed or canonicalized."
+);
+
+fn to_py_err(e: CanonicalizationError) -> PyErr {
+    CanonicalizationException::new_err(e.to_string())
+}
+
+fn parse(nquads: &str) -> Result<Dataset, CanonicalizationError> {
+    Ok(NQuadsParser::new()
+        .for_slice(nquads.as_bytes())
+        .collect::<Result<Dataset, _>>()?)
+}
+
+/// Returns the serialized canonical form of the N-Quads document,
+/// where any blank nodes are assigned deterministic identifiers.
+#[pyfunction]
+fn canonicalize(py: Python<'_>, nquads: &str) -> PyResult<String> {
+    py.allow_threads(|| rdf_canon::canonicalize(&parse(nquads)?))
+        .map_err(to_py_err)
+}
+
+/// Returns the map from the blank node identifiers in the N-Quads document
+/// to the canonical identifiers issued for them.
+#[pyfunction]
+fn issue(py: Python<'_>, nquads: &str) -> PyResult<HashMap<String, String>> {
+    py.allow_threads(|| rdf_canon::issue(&parse(nquads)?))
+        .map_err(to_py_err)
+}
+
+/// The `rdf_canon` Python module.
+#[pymodule]
+#[pyo3(name = "rdf_canon")]
+fn rdf_canon_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(canonicalize, m)?)?;
+    m.add_function(wrap_pyfunction!(issue, m)?)?;
+    m.add(
+        "CanonicalizationException",
+        m.py().get_type::<CanonicalizationException>(),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,31 @@
+import unittest
+
+import rdf_canon
+
+
+class TestRdfCanon(unittest.TestCase):
+    INPUT = (
+        "_:e0 <http://example.org/vocab#next> _:e1 .\n"
+        '_:e1 <http://example.org/vocab#label> "one" .\n'
+    )
+
+    def test_canonicalize(self):
+        self.assertEqual(
+            rdf_canon.canonicalize(self.INPUT),
+            '_:c14n0 <http://example.org/vocab#label> "one" .\n'
+            "_:c14n1 <http://example.org/vocab#next> _:c14n0 .\n",
+        )
+        self.assertEqual(rdf_canon.canonicalize(""), "")
+
+    def test_issue(self):
+        self.assertEqual(rdf_canon.issue(self.INPUT), {"e0": "c14n1", "e1": "c14n0"})
+
+    def test_error(self):
+        with self.assertRaises(rdf_canon.CanonicalizationException) as raised:
+            rdf_canon.canonicalize("_:e0 <http://example.org/vocab#next> .\n")
+        self.assertIsInstance(raised.exception, ValueError)
+        self.assertIn("line 1", str(raised.exception))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -1,15 +1,37 @@
 use crate::{
-    canon::{canonicalize_core, serialize, serialize_graph},
-    counter::{HndqCallCounter, SimpleHndqCallCounter},
+    canon::{
+        canonicalize_core, canonicalize_core_with_recording, first_degree_hashes, serialize,
+        serialize_graph, serialize_to_writer, CanonicalizationState, CanonicalizationStats,
+        ChosenPathTrace, IdentifierIssuer, Recording,
+    },
+    counter::{HndqCallCounter, MaybeSend, PerNodeHndqCallCounter, SimpleHndqCallCounter},
     CanonicalizationError,
 };
 use digest::Digest;
+use indexmap::IndexMap;
 use oxrdf::{
-    BlankNode, BlankNodeRef, Dataset, Graph, GraphName, GraphNameRef, Quad, QuadRef, Subject,
-    SubjectRef, Term, TermRef, Triple, TripleRef,
+    BlankNode, BlankNodeRef, Dataset, Graph, GraphName, GraphNameRef, Literal, NamedNode, Quad,
+    QuadRef, Subject, SubjectRef, Term, TermRef, Triple, TripleRef,
 };
-use sha2::Sha256;
-use std::collections::HashMap;
+use oxttl::{NQuadsParser, NTriplesParser, TriGParser, TurtleParser};
+use sha2::{Sha256, Sha384, Sha512};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt,
+    hash::BuildHasher,
+    io::{BufRead, BufReader, Read, Write},
+    str::FromStr,
+    sync::Arc,
+};
+
+#[cfg(feature = "rdf-star")]
+use crate::canon::quoted_blank_node_identifiers_in_quad;
+#[cfg(feature = "transcript")]
+use crate::transcript::TranscriptEntry;
+#[cfg(feature = "unicode-normalization")]
+use unicode_normalization::UnicodeNormalization;
 
 /// Returns the serialized canonical form of the canonicalized dataset,
 /// where any blank nodes in the input dataset are assigned deterministic identifiers.
@@ -21,7 +43,7 @@ use std::collections::HashMap;
 /// use oxttl::NQuadsParser;
 /// use rdf_canon::canonicalize;
 /// use std::io::Cursor;
-
+///
 /// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
 /// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
 /// _:e1 <http://example.org/vocab#next> _:e2 _:g .
@@ -62,7 +84,7 @@ pub fn canonicalize(input_dataset: &Dataset) -> Result<String, CanonicalizationE
 /// use oxttl::NTriplesParser;
 /// use rdf_canon::canonicalize_graph;
 /// use std::io::Cursor;
-
+///
 /// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
 /// _:e0 <http://example.org/vocab#prev> _:e2 .
 /// _:e1 <http://example.org/vocab#next> _:e2 .
@@ -103,7 +125,7 @@ pub fn canonicalize_graph(input_graph: &Graph) -> Result<String, Canonicalizatio
 /// use oxttl::NQuadsParser;
 /// use rdf_canon::canonicalize_quads;
 /// use std::io::Cursor;
-
+///
 /// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
 /// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
 /// _:e1 <http://example.org/vocab#next> _:e2 _:g .
@@ -134,9 +156,299 @@ pub fn canonicalize_quads(input_quads: &[Quad]) -> Result<String, Canonicalizati
     canonicalize_quads_with::<Sha256>(input_quads, &options)
 }
 
+/// Returns the serialized canonical form of the canonicalized graph,
+/// where any blank nodes in the input triples are assigned deterministic identifiers.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Triple;
+/// use oxttl::NTriplesParser;
+/// use rdf_canon::canonicalize_triples;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e0 <http://example.org/vocab#prev> _:e2 .
+/// _:e1 <http://example.org/vocab#next> _:e2 .
+/// _:e1 <http://example.org/vocab#prev> _:e0 .
+/// _:e2 <http://example.org/vocab#next> _:e0 .
+/// _:e2 <http://example.org/vocab#prev> _:e1 .
+/// "#;
+/// let expected = r#"_:c14n0 <http://example.org/vocab#next> _:c14n2 .
+/// _:c14n0 <http://example.org/vocab#prev> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#prev> _:c14n2 .
+/// _:c14n2 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n2 <http://example.org/vocab#prev> _:c14n0 .
+/// "#;
+///
+/// let input_triples: Vec<Triple> = NTriplesParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap())
+///     .collect();
+/// let canonicalized = canonicalize_triples(&input_triples).unwrap();
+///
+/// assert_eq!(canonicalized, expected);
+/// ```
+pub fn canonicalize_triples(input_triples: &[Triple]) -> Result<String, CanonicalizationError> {
+    let options = CanonicalizationOptions::default();
+    canonicalize_triples_with::<Sha256>(input_triples, &options)
+}
+
+/// Options of the canonicalization.
+/// Prefer constructing them with `CanonicalizationOptions::builder()`,
+/// which keeps working as new options are added;
+/// the public fields are kept for compatibility with struct literals.
 #[derive(Default)]
 pub struct CanonicalizationOptions {
     pub hndq_call_limit: Option<usize>,
+    /// The maximum number of quads in which a single blank node may appear,
+    /// i.e., the length of its entry in the blank node to quads map.
+    /// A blank node appearing in a huge number of quads, e.g., with distinct literals,
+    /// bloats the serialization hashed by the Hash First Degree Quads algorithm, which the
+    /// aggregate limits do not catch; exceeding the limit results in
+    /// `CanonicalizationError::BlankNodeTooConnected`.
+    /// Unlimited if not given.
+    pub max_quads_per_blank_node: Option<usize>,
+    /// How calls to the Hash N-Degree Quads algorithm are counted against `hndq_call_limit`.
+    pub counter_strategy: CounterStrategy,
+    pub prefix_collision: PrefixCollisionPolicy,
+    /// Lowercases the language tags of literals before canonicalizing,
+    /// so that datasets differing only in the casing of language tags,
+    /// which are case-insensitive in BCP 47, have the same canonical form.
+    /// This is a non-standard normalization and disabled by default.
+    pub normalize_language_tags: bool,
+    /// Normalizes the values and language tags of literals to the given Unicode
+    /// normalization form before canonicalizing, so that datasets whose literals
+    /// differ only in, e.g., composed and decomposed accented letters have the same
+    /// canonical form.
+    /// This is a non-standard normalization and disabled by default.
+    #[cfg(feature = "unicode-normalization")]
+    pub unicode_normalization: Option<NormForm>,
+    /// The prefix of canonical identifiers, e.g., `doc1_c14n` to issue `doc1_c14n0`,
+    /// `doc1_c14n1`, ... when merging canonicalized datasets from several sources.
+    /// Defaults to `c14n` if not given.
+    /// The identifiers are relabeled after the canonicalization algorithm runs,
+    /// so the prefix changes the labels but not which blank node gets which counter.
+    /// Note that the canonical form then deviates from the specification.
+    pub canonical_prefix: Option<String>,
+    /// Formats the canonical identifier of each blank node from the counter of the
+    /// canonical issuer, e.g., `Arc::new(|n| format!("b{:04}", n))`.
+    /// The labels must be valid and distinct blank node identifiers, otherwise
+    /// `CanonicalizationError::InvalidCanonicalLabel` is returned.
+    /// This takes precedence over `canonical_prefix`.
+    /// If not given, labels are formatted as `c14n0`, `c14n1`, ...
+    /// with the prefix chosen according to `canonical_prefix` and `prefix_collision`.
+    /// Note that the canonical form then deviates from the specification
+    /// unless the default labels are reproduced.
+    pub label_formatter: Option<Arc<dyn Fn(usize) -> String + Send + Sync>>,
+    /// Called with `(buckets_done, total_buckets)` after each entry of the hash to blank nodes
+    /// map is processed in step 5 of the canonicalization algorithm, where the expensive
+    /// Hash N-Degree Quads algorithm runs, e.g., to show the progress as a percentage.
+    /// It is called in the order of processing, and not at all if every blank node
+    /// has a unique first degree hash.
+    pub progress: Option<Arc<dyn Fn(usize, usize) + Send + Sync>>,
+}
+
+impl CanonicalizationOptions {
+    /// Returns a builder of options, starting from the defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oxrdf::Dataset;
+    /// use oxttl::NQuadsParser;
+    /// use rdf_canon::{canonicalize_with, CanonicalizationOptions};
+    /// use sha2::Sha256;
+    /// use std::io::Cursor;
+    ///
+    /// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+    /// _:e1 <http://example.org/vocab#next> _:e0 .
+    /// "#;
+    /// let input_quads = NQuadsParser::new()
+    ///     .for_reader(Cursor::new(input))
+    ///     .map(|x| x.unwrap());
+    /// let input_dataset = Dataset::from_iter(input_quads);
+    ///
+    /// let built = CanonicalizationOptions::builder()
+    ///     .hndq_call_limit(10000)
+    ///     .build();
+    /// let literal = CanonicalizationOptions {
+    ///     hndq_call_limit: Some(10000),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(built.hndq_call_limit, literal.hndq_call_limit);
+    /// assert_eq!(
+    ///     canonicalize_with::<Sha256>(&input_dataset, &built).unwrap(),
+    ///     canonicalize_with::<Sha256>(&input_dataset, &literal).unwrap()
+    /// );
+    ///
+    /// let prefixed = CanonicalizationOptions::builder()
+    ///     .canonical_prefix("doc1_c14n")
+    ///     .build();
+    /// assert_eq!(
+    ///     canonicalize_with::<Sha256>(&input_dataset, &prefixed).unwrap(),
+    ///     r#"_:doc1_c14n0 <http://example.org/vocab#next> _:doc1_c14n1 .
+    /// _:doc1_c14n1 <http://example.org/vocab#next> _:doc1_c14n0 .
+    /// "#
+    /// );
+    /// ```
+    pub fn builder() -> CanonicalizationOptionsBuilder {
+        CanonicalizationOptionsBuilder::default()
+    }
+}
+
+/// A builder of `CanonicalizationOptions`, created by `CanonicalizationOptions::builder()`.
+/// Each method sets the option of the same name; see the fields of
+/// `CanonicalizationOptions` for their meaning.
+#[derive(Default)]
+pub struct CanonicalizationOptionsBuilder {
+    options: CanonicalizationOptions,
+}
+
+impl CanonicalizationOptionsBuilder {
+    pub fn hndq_call_limit(mut self, limit: usize) -> Self {
+        self.options.hndq_call_limit = Some(limit);
+        self
+    }
+
+    pub fn max_quads_per_blank_node(mut self, limit: usize) -> Self {
+        self.options.max_quads_per_blank_node = Some(limit);
+        self
+    }
+
+    pub fn counter_strategy(mut self, strategy: CounterStrategy) -> Self {
+        self.options.counter_strategy = strategy;
+        self
+    }
+
+    pub fn prefix_collision(mut self, policy: PrefixCollisionPolicy) -> Self {
+        self.options.prefix_collision = policy;
+        self
+    }
+
+    pub fn normalize_language_tags(mut self, normalize: bool) -> Self {
+        self.options.normalize_language_tags = normalize;
+        self
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    pub fn unicode_normalization(mut self, form: NormForm) -> Self {
+        self.options.unicode_normalization = Some(form);
+        self
+    }
+
+    pub fn canonical_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.options.canonical_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn label_formatter(
+        mut self,
+        formatter: impl Fn(usize) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.options.label_formatter = Some(Arc::new(formatter));
+        self
+    }
+
+    pub fn progress(mut self, progress: impl Fn(usize, usize) + Send + Sync + 'static) -> Self {
+        self.options.progress = Some(Arc::new(progress));
+        self
+    }
+
+    pub fn build(self) -> CanonicalizationOptions {
+        self.options
+    }
+}
+
+/// How to handle input blank node identifiers that look like canonical
+/// identifiers (e.g., `_:c14n0`), which may be confused with the issued ones
+/// although they generally denote different blank nodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PrefixCollisionPolicy {
+    /// Relabel such blank nodes like any other, as the specification does.
+    /// This is the default.
+    #[default]
+    Allow,
+    /// Fail with `CanonicalizationError::PrefixCollision`.
+    Error,
+    /// Issue canonical identifiers with the first of `c14n_`, `c14n__`, ...
+    /// (or the canonical prefix in the options followed by underscores)
+    /// that no input blank node identifier collides with.
+    /// Note that the canonical form then deviates from the specification.
+    FreshPrefix,
+}
+
+/// How to count calls to the Hash N-Degree Quads algorithm against the call limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CounterStrategy {
+    /// Count all calls together with `SimpleHndqCallCounter`.
+    /// This is the default.
+    #[default]
+    Global,
+    /// Count the calls for each blank node separately with `PerNodeHndqCallCounter`,
+    /// which bounds the work spent on any single blank node
+    /// while allowing large datasets with many blank nodes to need more calls in total.
+    PerNode,
+}
+
+/// A Unicode normalization form for the `unicode_normalization` option.
+#[cfg(feature = "unicode-normalization")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NormForm {
+    /// Normalization Form C (canonical composition)
+    Nfc,
+    /// Normalization Form D (canonical decomposition)
+    Nfd,
+}
+
+#[cfg(feature = "unicode-normalization")]
+impl NormForm {
+    /// Returns the string in this normalization form, or `None` if it already is.
+    fn normalize(self, s: &str) -> Option<String> {
+        let normalized: String = match self {
+            Self::Nfc => s.nfc().collect(),
+            Self::Nfd => s.nfd().collect(),
+        };
+        (normalized != s).then_some(normalized)
+    }
+}
+
+/// A hash algorithm to be chosen at runtime,
+/// e.g., from the `hashAlgorithm` of a test manifest entry.
+/// Parsed from names such as `SHA256` or `SHA-384`, ignoring case.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    /// SHA-256, the default hash algorithm of RDFC-1.0.
+    #[default]
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = CanonicalizationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "SHA256" | "SHA-256" => Ok(Self::Sha256),
+            "SHA384" | "SHA-384" => Ok(Self::Sha384),
+            "SHA512" | "SHA-512" => Ok(Self::Sha512),
+            _ => Err(CanonicalizationError::UnsupportedHashAlgorithm(
+                s.to_string(),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sha256 => write!(f, "SHA256"),
+            Self::Sha384 => write!(f, "SHA384"),
+            Self::Sha512 => write!(f, "SHA512"),
+        }
+    }
 }
 
 /// Given some options (e.g., call limit),
@@ -151,7 +463,7 @@ pub struct CanonicalizationOptions {
 /// use rdf_canon::{canonicalize_with, CanonicalizationOptions};
 /// use sha2::Sha256;
 /// use std::io::Cursor;
-
+///
 /// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
 /// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
 /// _:e1 <http://example.org/vocab#next> _:e2 _:g .
@@ -175,6 +487,7 @@ pub struct CanonicalizationOptions {
 /// let input_dataset = Dataset::from_iter(input_quads);
 /// let options = CanonicalizationOptions {
 ///     hndq_call_limit: Some(10000),
+///     ..Default::default()
 /// };
 /// let canonicalized = canonicalize_with::<Sha256>(&input_dataset, &options).unwrap();
 ///
@@ -184,74 +497,68 @@ pub fn canonicalize_with<D: Digest>(
     input_dataset: &Dataset,
     options: &CanonicalizationOptions,
 ) -> Result<String, CanonicalizationError> {
-    let issued_identifiers_map = issue_with::<D>(input_dataset, options)?;
-    let relabeled_dataset = relabel(input_dataset, &issued_identifiers_map)?;
+    let relabeled_dataset = canonicalize_to_cow::<D>(input_dataset, options)?;
     Ok(serialize(&relabeled_dataset))
 }
 
 /// Given some options (e.g., call limit),
-/// returns the serialized canonical form of the canonicalized dataset,
-/// where any blank nodes in the input graph are assigned deterministic identifiers.
+/// returns the serialized canonical form of the canonicalized dataset as UTF-8 bytes,
+/// e.g., to be fed into a hash function or a signature scheme.
+/// The output is identical to the bytes of the string returned by `canonicalize_with`.
 ///
 /// # Examples
 ///
 /// ```
-/// use oxrdf::Graph;
-/// use oxttl::NTriplesParser;
-/// use rdf_canon::{canonicalize_graph_with, CanonicalizationOptions};
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{canonicalize_to_bytes, canonicalize_with, CanonicalizationOptions};
 /// use sha2::Sha256;
 /// use std::io::Cursor;
-
+///
 /// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
-/// _:e0 <http://example.org/vocab#prev> _:e2 .
-/// _:e1 <http://example.org/vocab#next> _:e2 .
-/// _:e1 <http://example.org/vocab#prev> _:e0 .
-/// _:e2 <http://example.org/vocab#next> _:e0 .
-/// _:e2 <http://example.org/vocab#prev> _:e1 .
-/// <urn:ex:s> <urn:ex:p> "\u0008\u0009\u000a\u000b\u000c\u000d\u0022\u005c\u007f" .
+/// _:e1 <http://example.org/vocab#label> "one" .
 /// "#;
-/// let expected = r#"<urn:ex:s> <urn:ex:p> "\b\t\n\u000B\f\r\"\\\u007F" .
-/// _:c14n0 <http://example.org/vocab#next> _:c14n2 .
-/// _:c14n0 <http://example.org/vocab#prev> _:c14n1 .
+/// let expected = r#"_:c14n0 <http://example.org/vocab#label> "one" .
 /// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
-/// _:c14n1 <http://example.org/vocab#prev> _:c14n2 .
-/// _:c14n2 <http://example.org/vocab#next> _:c14n1 .
-/// _:c14n2 <http://example.org/vocab#prev> _:c14n0 .
 /// "#;
 ///
-/// let input_triples = NTriplesParser::new()
+/// let input_quads = NQuadsParser::new()
 ///     .for_reader(Cursor::new(input))
 ///     .map(|x| x.unwrap());
-/// let input_graph = Graph::from_iter(input_triples);
-/// let options = CanonicalizationOptions {
-///     hndq_call_limit: Some(10000),
-/// };
-/// let canonicalized = canonicalize_graph_with::<Sha256>(&input_graph, &options).unwrap();
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let options = CanonicalizationOptions::default();
+/// let canonicalized = canonicalize_to_bytes::<Sha256>(&input_dataset, &options).unwrap();
 ///
-/// assert_eq!(canonicalized, expected);
+/// assert_eq!(canonicalized, expected.as_bytes());
+/// assert_eq!(
+///     canonicalized,
+///     canonicalize_with::<Sha256>(&input_dataset, &options)
+///         .unwrap()
+///         .into_bytes()
+/// );
 /// ```
-pub fn canonicalize_graph_with<D: Digest>(
-    input_graph: &Graph,
+pub fn canonicalize_to_bytes<D: Digest>(
+    input_dataset: &Dataset,
     options: &CanonicalizationOptions,
-) -> Result<String, CanonicalizationError> {
-    let issued_identifiers_map = issue_graph_with::<D>(input_graph, options)?;
-    let relabeled_graph = relabel_graph(input_graph, &issued_identifiers_map)?;
-    Ok(serialize_graph(&relabeled_graph))
+) -> Result<Vec<u8>, CanonicalizationError> {
+    let relabeled_dataset = canonicalize_to_cow::<D>(input_dataset, options)?;
+    let mut buffer = Vec::new();
+    serialize_to_writer(&relabeled_dataset, &mut buffer)?;
+    Ok(buffer)
 }
 
-/// Given some options (e.g., call limit),
+/// Given a hash algorithm chosen at runtime and some options (e.g., call limit),
 /// returns the serialized canonical form of the canonicalized dataset,
-/// where any blank nodes in the input quads are assigned deterministic identifiers.
+/// where any blank nodes in the input dataset are assigned deterministic identifiers.
 ///
 /// # Examples
 ///
 /// ```
-/// use oxrdf::Quad;
+/// use oxrdf::Dataset;
 /// use oxttl::NQuadsParser;
-/// use rdf_canon::{canonicalize_quads_with, CanonicalizationOptions};
-/// use sha2::Sha256;
+/// use rdf_canon::{canonicalize_with_algorithm, CanonicalizationOptions, HashAlgorithm};
 /// use std::io::Cursor;
-
+///
 /// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
 /// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
 /// _:e1 <http://example.org/vocab#next> _:e2 _:g .
@@ -261,230 +568,293 @@ pub fn canonicalize_graph_with<D: Digest>(
 /// <urn:ex:s> <urn:ex:p> "\u0008\u0009\u000a\u000b\u000c\u000d\u0022\u005c\u007f" _:g .
 /// "#;
 /// let expected = r#"<urn:ex:s> <urn:ex:p> "\b\t\n\u000B\f\r\"\\\u007F" _:c14n0 .
-/// _:c14n1 <http://example.org/vocab#next> _:c14n2 _:c14n0 .
-/// _:c14n1 <http://example.org/vocab#prev> _:c14n3 _:c14n0 .
-/// _:c14n2 <http://example.org/vocab#next> _:c14n3 _:c14n0 .
-/// _:c14n2 <http://example.org/vocab#prev> _:c14n1 _:c14n0 .
-/// _:c14n3 <http://example.org/vocab#next> _:c14n1 _:c14n0 .
-/// _:c14n3 <http://example.org/vocab#prev> _:c14n2 _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n3 _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#prev> _:c14n2 _:c14n0 .
+/// _:c14n2 <http://example.org/vocab#next> _:c14n1 _:c14n0 .
+/// _:c14n2 <http://example.org/vocab#prev> _:c14n3 _:c14n0 .
+/// _:c14n3 <http://example.org/vocab#next> _:c14n2 _:c14n0 .
+/// _:c14n3 <http://example.org/vocab#prev> _:c14n1 _:c14n0 .
 /// "#;
 ///
-/// let input_quads: Vec<Quad> = NQuadsParser::new()
+/// let input_quads = NQuadsParser::new()
 ///     .for_reader(Cursor::new(input))
-///     .map(|x| x.unwrap())
-///     .collect();
-/// let options = CanonicalizationOptions {
-///     hndq_call_limit: Some(10000),
-/// };
-/// let canonicalized = canonicalize_quads_with::<Sha256>(&input_quads, &options).unwrap();
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let algorithm: HashAlgorithm = "SHA384".parse().unwrap();
+/// let options = CanonicalizationOptions::default();
+/// let canonicalized =
+///     canonicalize_with_algorithm(&input_dataset, algorithm, &options).unwrap();
 ///
 /// assert_eq!(canonicalized, expected);
 /// ```
-pub fn canonicalize_quads_with<D: Digest>(
-    input_quads: &[Quad],
+pub fn canonicalize_with_algorithm(
+    input_dataset: &Dataset,
+    algorithm: HashAlgorithm,
     options: &CanonicalizationOptions,
 ) -> Result<String, CanonicalizationError> {
-    let input_dataset = Dataset::from_iter(input_quads);
-    let issued_identifiers_map = issue_with::<D>(&input_dataset, options)?;
-    let relabeled_dataset = relabel(&input_dataset, &issued_identifiers_map)?;
-    Ok(serialize(&relabeled_dataset))
+    match algorithm {
+        HashAlgorithm::Sha256 => canonicalize_with::<Sha256>(input_dataset, options),
+        HashAlgorithm::Sha384 => canonicalize_with::<Sha384>(input_dataset, options),
+        HashAlgorithm::Sha512 => canonicalize_with::<Sha512>(input_dataset, options),
+    }
 }
 
-/// Assigns deterministic identifiers to any blank nodes in the input dataset
-/// and returns the assignment result as a map.
+/// Given some options (e.g., call limit),
+/// returns the canonicalized dataset itself rather than its serialized canonical form,
+/// where any blank nodes in the input dataset are assigned deterministic identifiers.
+/// Note that the output `Dataset` does not retain the order of quads, as with `relabel`;
+/// use `serialize` to obtain them in canonical order.
 ///
 /// # Examples
 ///
 /// ```
 /// use oxrdf::Dataset;
 /// use oxttl::NQuadsParser;
-/// use rdf_canon::issue;
-/// use std::collections::HashMap;
+/// use rdf_canon::{canonicalize_to_dataset, canonicalize_with, serialize, CanonicalizationOptions};
+/// use sha2::Sha256;
 /// use std::io::Cursor;
 ///
-/// let input = r#"
-/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
-/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
-/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
-/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
-/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
-/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// _:e1 <http://example.org/vocab#label> "one" .
 /// "#;
-/// let expected_map = HashMap::from([
-///     ("g".to_string(), "c14n0".to_string()),
-///     ("e0".to_string(), "c14n1".to_string()),
-///     ("e1".to_string(), "c14n2".to_string()),
-///     ("e2".to_string(), "c14n3".to_string()),
-/// ]);
 ///
 /// let input_quads = NQuadsParser::new()
 ///     .for_reader(Cursor::new(input))
 ///     .map(|x| x.unwrap());
 /// let input_dataset = Dataset::from_iter(input_quads);
-/// let issued_identifiers_map = issue(&input_dataset).unwrap();
+/// let options = CanonicalizationOptions::default();
+/// let canonicalized_dataset =
+///     canonicalize_to_dataset::<Sha256>(&input_dataset, &options).unwrap();
 ///
-/// assert_eq!(issued_identifiers_map, expected_map);
+/// assert_eq!(
+///     serialize(&canonicalized_dataset),
+///     canonicalize_with::<Sha256>(&input_dataset, &options).unwrap()
+/// );
 /// ```
-pub fn issue(input_dataset: &Dataset) -> Result<HashMap<String, String>, CanonicalizationError> {
-    let options = CanonicalizationOptions::default();
-    issue_with::<Sha256>(input_dataset, &options)
+pub fn canonicalize_to_dataset<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<Dataset, CanonicalizationError> {
+    Ok(canonicalize_to_cow::<D>(input_dataset, options)?.into_owned())
 }
 
-/// Assigns deterministic identifiers to any blank nodes in the input graph
-/// and returns the assignment result as a map.
+/// Returns the canonicalized dataset, borrowing the input dataset
+/// if it has no blank nodes to relabel and no normalization applies.
+fn canonicalize_to_cow<'a, D: Digest>(
+    input_dataset: &'a Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<Cow<'a, Dataset>, CanonicalizationError> {
+    let input_dataset = normalize_dataset(input_dataset, options);
+    let issued_identifiers_map = issue_normalized_map::<D>(&input_dataset, options)?;
+    if issued_identifiers_map.is_empty() {
+        return Ok(input_dataset);
+    }
+    Ok(Cow::Owned(relabel(
+        &input_dataset,
+        &issued_identifiers_map,
+    )?))
+}
+
+/// Returns the canonicalized dataset extending a previously canonicalized dataset
+/// with some additional quads, along with the issued identifiers map from the blank node
+/// identifiers in `previous` and `additions` to the new canonical identifiers.
+/// Blank nodes in `additions` with the same identifier as a blank node in `previous`,
+/// e.g., `_:c14n0`, denote that blank node.
+///
+/// If the additions have no blank nodes, the previous canonical identifiers are reused
+/// without running the canonicalization algorithm again. This is sound because the hashes
+/// that determine the canonical identifiers of blank nodes only depend on the quads in which
+/// blank nodes appear, which ground quads do not change. Any other additions may change
+/// the hashes, or the order in which the identifiers are issued, of blank nodes anywhere in
+/// the dataset, so the extended dataset is then canonicalized from scratch.
+///
+/// `previous` must be the output of canonicalization with the default options
+/// (i.e., SHA-256 and the `c14n` prefix), otherwise the result is not canonical.
 ///
 /// # Examples
 ///
 /// ```
-/// use oxrdf::Graph;
-/// use oxttl::NTriplesParser;
-/// use rdf_canon::issue_graph;
-/// use std::collections::HashMap;
+/// use oxrdf::{Dataset, Quad};
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{canonicalize, recanonicalize, serialize};
 /// use std::io::Cursor;
 ///
-/// let input = r#"
-/// _:e0 <http://example.org/vocab#next> _:e1 .
-/// _:e0 <http://example.org/vocab#prev> _:e2 .
-/// _:e1 <http://example.org/vocab#next> _:e2 .
-/// _:e1 <http://example.org/vocab#prev> _:e0 .
-/// _:e2 <http://example.org/vocab#next> _:e0 .
-/// _:e2 <http://example.org/vocab#prev> _:e1 .
+/// let previous = r#"_:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#;
+/// let additions = r#"<urn:ex:s> <urn:ex:p> <urn:ex:o> .
 /// "#;
-/// let expected_map = HashMap::from([
-///     ("e0".to_string(), "c14n0".to_string()),
-///     ("e1".to_string(), "c14n2".to_string()),
-///     ("e2".to_string(), "c14n1".to_string()),
-/// ]);
 ///
-/// let input_triples = NTriplesParser::new()
-///     .for_reader(Cursor::new(input))
-///     .map(|x| x.unwrap());
-/// let input_graph = Graph::from_iter(input_triples);
-/// let issued_identifiers_map = issue_graph(&input_graph).unwrap();
+/// let previous = Dataset::from_iter(
+///     NQuadsParser::new()
+///         .for_reader(Cursor::new(previous))
+///         .map(|x| x.unwrap()),
+/// );
+/// let additions: Vec<Quad> = NQuadsParser::new()
+///     .for_reader(Cursor::new(additions))
+///     .map(|x| x.unwrap())
+///     .collect();
+/// let (extended, issued_identifiers_map) = recanonicalize(&previous, &additions).unwrap();
 ///
-/// assert_eq!(issued_identifiers_map, expected_map);
+/// assert_eq!(issued_identifiers_map["c14n0"], "c14n0");
+/// let mut expected = previous.clone();
+/// expected.extend(&additions);
+/// assert_eq!(serialize(&extended), canonicalize(&expected).unwrap());
 /// ```
-pub fn issue_graph(input_graph: &Graph) -> Result<HashMap<String, String>, CanonicalizationError> {
-    let options = CanonicalizationOptions::default();
-    issue_graph_with::<Sha256>(input_graph, &options)
+pub fn recanonicalize(
+    previous: &Dataset,
+    additions: &[Quad],
+) -> Result<(Dataset, HashMap<String, String>), CanonicalizationError> {
+    let mut extended = previous.clone();
+    extended.extend(additions);
+
+    if additions
+        .iter()
+        .all(|q| blank_node_identifiers_in_quad(q.into()).is_empty())
+    {
+        let issued_identifiers_map = blank_node_identifiers(previous)
+            .into_iter()
+            .map(|id| (id.to_string(), id.to_string()))
+            .collect();
+        return Ok((extended, issued_identifiers_map));
+    }
+
+    let issued_identifiers_map = issue(&extended)?;
+    let relabeled_dataset = relabel(&extended, &issued_identifiers_map)?;
+    Ok((relabeled_dataset, issued_identifiers_map))
 }
 
-/// Assigns deterministic identifiers to any blank nodes in the input quads
-/// and returns the assignment result as a map.
+/// Given some options (e.g., call limit),
+/// returns the quads of the canonicalized dataset sorted into code point order
+/// of their canonical n-quads forms, without serializing the whole dataset,
+/// e.g., to re-serialize them in another RDF syntax in canonical order.
 ///
 /// # Examples
 ///
 /// ```
-/// use oxrdf::Quad;
+/// use oxrdf::Dataset;
 /// use oxttl::NQuadsParser;
-/// use rdf_canon::issue_quads;
-/// use std::collections::HashMap;
+/// use rdf_canon::{canonicalize_to_sorted_quads, canonicalize_with, CanonicalizationOptions};
+/// use sha2::Sha256;
 /// use std::io::Cursor;
 ///
-/// let input = r#"
-/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
-/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
-/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
-/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
-/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
-/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// _:e1 <http://example.org/vocab#label> "one" .
 /// "#;
-/// let expected_map = HashMap::from([
-///     ("g".to_string(), "c14n0".to_string()),
-///     ("e0".to_string(), "c14n1".to_string()),
-///     ("e1".to_string(), "c14n2".to_string()),
-///     ("e2".to_string(), "c14n3".to_string()),
-/// ]);
 ///
-/// let input_quads: Vec<Quad> = NQuadsParser::new()
+/// let input_quads = NQuadsParser::new()
 ///     .for_reader(Cursor::new(input))
-///     .map(|x| x.unwrap())
-///     .collect();
-/// let issued_identifiers_map = issue_quads(&input_quads).unwrap();
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let options = CanonicalizationOptions::default();
+/// let sorted_quads = canonicalize_to_sorted_quads::<Sha256>(&input_dataset, &options).unwrap();
 ///
-/// assert_eq!(issued_identifiers_map, expected_map);
+/// let serialized: String = sorted_quads.iter().map(|q| format!("{} .\n", q)).collect();
+/// assert_eq!(
+///     serialized,
+///     canonicalize_with::<Sha256>(&input_dataset, &options).unwrap()
+/// );
 /// ```
-pub fn issue_quads(input_quads: &[Quad]) -> Result<HashMap<String, String>, CanonicalizationError> {
-    let options = CanonicalizationOptions::default();
-    issue_quads_with::<Sha256>(input_quads, &options)
+pub fn canonicalize_to_sorted_quads<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<Vec<Quad>, CanonicalizationError> {
+    let relabeled_dataset = canonicalize_to_dataset::<D>(input_dataset, options)?;
+    Ok(sort(&relabeled_dataset))
+}
+
+/// The issued identifiers map, serialized canonical form, and canonicalized dataset
+/// obtained from a single run of the canonicalization algorithm.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CanonicalizationResult {
+    /// The map from blank node identifiers in the input dataset to canonical identifiers
+    pub issued_identifiers_map: HashMap<String, String>,
+    /// The serialized canonical form, i.e., canonical N-Quads
+    pub canonical_nquads: String,
+    /// The canonicalized dataset, which does not retain the order of quads
+    pub canonical_dataset: Dataset,
 }
 
 /// Given some options (e.g., call limit),
-/// assigns deterministic identifiers to any blank nodes in the input dataset
-/// and returns the assignment result as a map.
+/// returns the issued identifiers map, the serialized canonical form,
+/// and the canonicalized dataset at once, running the canonicalization algorithm only once.
 ///
 /// # Examples
 ///
 /// ```
 /// use oxrdf::Dataset;
 /// use oxttl::NQuadsParser;
-/// use rdf_canon::{issue_with, CanonicalizationOptions};
+/// use rdf_canon::{canonicalize_full, canonicalize_with, issue_with, CanonicalizationOptions};
 /// use sha2::Sha256;
-/// use std::collections::HashMap;
 /// use std::io::Cursor;
 ///
-/// let input = r#"
-/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
-/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
-/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
-/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
-/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
-/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// _:e1 <http://example.org/vocab#label> "one" .
 /// "#;
-/// let expected_map = HashMap::from([
-///     ("g".to_string(), "c14n0".to_string()),
-///     ("e0".to_string(), "c14n1".to_string()),
-///     ("e1".to_string(), "c14n2".to_string()),
-///     ("e2".to_string(), "c14n3".to_string()),
-/// ]);
 ///
 /// let input_quads = NQuadsParser::new()
 ///     .for_reader(Cursor::new(input))
 ///     .map(|x| x.unwrap());
 /// let input_dataset = Dataset::from_iter(input_quads);
-/// let options = CanonicalizationOptions {
-///     hndq_call_limit: Some(10000),
-/// };
-///
-/// let issued_identifiers_map = issue_with::<Sha256>(&input_dataset, &options).unwrap();
+/// let options = CanonicalizationOptions::default();
+/// let result = canonicalize_full::<Sha256>(&input_dataset, &options).unwrap();
 ///
-/// assert_eq!(issued_identifiers_map, expected_map);
+/// assert_eq!(
+///     result.issued_identifiers_map,
+///     issue_with::<Sha256>(&input_dataset, &options).unwrap()
+/// );
+/// assert_eq!(
+///     result.canonical_nquads,
+///     canonicalize_with::<Sha256>(&input_dataset, &options).unwrap()
+/// );
+/// assert_eq!(result.canonical_dataset.len(), 3);
 /// ```
-pub fn issue_with<D: Digest>(
+pub fn canonicalize_full<D: Digest>(
     input_dataset: &Dataset,
     options: &CanonicalizationOptions,
-) -> Result<HashMap<String, String>, CanonicalizationError> {
-    let hndq_call_counter = SimpleHndqCallCounter::new(options.hndq_call_limit);
-    canonicalize_core::<D>(input_dataset, hndq_call_counter)
+) -> Result<CanonicalizationResult, CanonicalizationError> {
+    let input_dataset = normalize_dataset(input_dataset, options);
+    let issued_identifiers_map = issue_normalized_map::<D>(&input_dataset, options)?;
+    let canonical_dataset = relabel(&input_dataset, &issued_identifiers_map)?;
+    Ok(CanonicalizationResult {
+        issued_identifiers_map,
+        canonical_nquads: serialize(&canonical_dataset),
+        canonical_dataset,
+    })
 }
 
 /// Given some options (e.g., call limit),
-/// assigns deterministic identifiers to any blank nodes in the input graph
-/// and returns the assignment result as a map.
+/// returns the serialized canonical form of the canonicalized dataset,
+/// where any blank nodes in the input graph are assigned deterministic identifiers.
 ///
 /// # Examples
 ///
 /// ```
 /// use oxrdf::Graph;
 /// use oxttl::NTriplesParser;
-/// use rdf_canon::{issue_graph_with, CanonicalizationOptions};
+/// use rdf_canon::{canonicalize_graph_with, CanonicalizationOptions};
 /// use sha2::Sha256;
-/// use std::collections::HashMap;
 /// use std::io::Cursor;
 ///
-/// let input = r#"
-/// _:e0 <http://example.org/vocab#next> _:e1 .
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
 /// _:e0 <http://example.org/vocab#prev> _:e2 .
 /// _:e1 <http://example.org/vocab#next> _:e2 .
 /// _:e1 <http://example.org/vocab#prev> _:e0 .
 /// _:e2 <http://example.org/vocab#next> _:e0 .
 /// _:e2 <http://example.org/vocab#prev> _:e1 .
+/// <urn:ex:s> <urn:ex:p> "\u0008\u0009\u000a\u000b\u000c\u000d\u0022\u005c\u007f" .
+/// "#;
+/// let expected = r#"<urn:ex:s> <urn:ex:p> "\b\t\n\u000B\f\r\"\\\u007F" .
+/// _:c14n0 <http://example.org/vocab#next> _:c14n2 .
+/// _:c14n0 <http://example.org/vocab#prev> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#prev> _:c14n2 .
+/// _:c14n2 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n2 <http://example.org/vocab#prev> _:c14n0 .
 /// "#;
-/// let expected_map = HashMap::from([
-///     ("e0".to_string(), "c14n0".to_string()),
-///     ("e1".to_string(), "c14n2".to_string()),
-///     ("e2".to_string(), "c14n1".to_string()),
-/// ]);
 ///
 /// let input_triples = NTriplesParser::new()
 ///     .for_reader(Cursor::new(input))
@@ -492,53 +862,56 @@ pub fn issue_with<D: Digest>(
 /// let input_graph = Graph::from_iter(input_triples);
 /// let options = CanonicalizationOptions {
 ///     hndq_call_limit: Some(10000),
+///     ..Default::default()
 /// };
+/// let canonicalized = canonicalize_graph_with::<Sha256>(&input_graph, &options).unwrap();
 ///
-/// let issued_identifiers_map = issue_graph_with::<Sha256>(&input_graph, &options).unwrap();
-///
-/// assert_eq!(issued_identifiers_map, expected_map);
+/// assert_eq!(canonicalized, expected);
 /// ```
-pub fn issue_graph_with<D: Digest>(
+pub fn canonicalize_graph_with<D: Digest>(
     input_graph: &Graph,
     options: &CanonicalizationOptions,
-) -> Result<HashMap<String, String>, CanonicalizationError> {
-    let hndq_call_counter = SimpleHndqCallCounter::new(options.hndq_call_limit);
+) -> Result<String, CanonicalizationError> {
+    let input_graph = normalize_graph(input_graph, options);
     let input_dataset = Dataset::from_iter(
         input_graph
             .iter()
             .map(|t| QuadRef::new(t.subject, t.predicate, t.object, GraphNameRef::DefaultGraph)),
     );
-    canonicalize_core::<D>(&input_dataset, hndq_call_counter)
+    let issued_identifiers_map = issue_normalized_map::<D>(&input_dataset, options)?;
+    let relabeled_graph = relabel_graph(&input_graph, &issued_identifiers_map)?;
+    Ok(serialize_graph(&relabeled_graph))
 }
 
 /// Given some options (e.g., call limit),
-/// assigns deterministic identifiers to any blank nodes in the input quads
-/// and returns the assignment result as a map.
+/// returns the serialized canonical form of the canonicalized dataset,
+/// where any blank nodes in the input quads are assigned deterministic identifiers.
 ///
 /// # Examples
 ///
 /// ```
 /// use oxrdf::Quad;
 /// use oxttl::NQuadsParser;
-/// use rdf_canon::{issue_quads_with, CanonicalizationOptions};
+/// use rdf_canon::{canonicalize_quads_with, CanonicalizationOptions};
 /// use sha2::Sha256;
-/// use std::collections::HashMap;
 /// use std::io::Cursor;
 ///
-/// let input = r#"
-/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
 /// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
 /// _:e1 <http://example.org/vocab#next> _:e2 _:g .
 /// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
 /// _:e2 <http://example.org/vocab#next> _:e0 _:g .
 /// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// <urn:ex:s> <urn:ex:p> "\u0008\u0009\u000a\u000b\u000c\u000d\u0022\u005c\u007f" _:g .
+/// "#;
+/// let expected = r#"<urn:ex:s> <urn:ex:p> "\b\t\n\u000B\f\r\"\\\u007F" _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n2 _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#prev> _:c14n3 _:c14n0 .
+/// _:c14n2 <http://example.org/vocab#next> _:c14n3 _:c14n0 .
+/// _:c14n2 <http://example.org/vocab#prev> _:c14n1 _:c14n0 .
+/// _:c14n3 <http://example.org/vocab#next> _:c14n1 _:c14n0 .
+/// _:c14n3 <http://example.org/vocab#prev> _:c14n2 _:c14n0 .
 /// "#;
-/// let expected_map = HashMap::from([
-///     ("g".to_string(), "c14n0".to_string()),
-///     ("e0".to_string(), "c14n1".to_string()),
-///     ("e1".to_string(), "c14n2".to_string()),
-///     ("e2".to_string(), "c14n3".to_string()),
-/// ]);
 ///
 /// let input_quads: Vec<Quad> = NQuadsParser::new()
 ///     .for_reader(Cursor::new(input))
@@ -546,277 +919,573 @@ pub fn issue_graph_with<D: Digest>(
 ///     .collect();
 /// let options = CanonicalizationOptions {
 ///     hndq_call_limit: Some(10000),
+///     ..Default::default()
 /// };
+/// let canonicalized = canonicalize_quads_with::<Sha256>(&input_quads, &options).unwrap();
 ///
-/// let issued_identifiers_map = issue_quads_with::<Sha256>(&input_quads, &options).unwrap();
-///
-/// assert_eq!(issued_identifiers_map, expected_map);
+/// assert_eq!(canonicalized, expected);
 /// ```
-pub fn issue_quads_with<D: Digest>(
+pub fn canonicalize_quads_with<D: Digest>(
     input_quads: &[Quad],
     options: &CanonicalizationOptions,
-) -> Result<HashMap<String, String>, CanonicalizationError> {
+) -> Result<String, CanonicalizationError> {
     let input_dataset = Dataset::from_iter(input_quads);
-    let hndq_call_counter = SimpleHndqCallCounter::new(options.hndq_call_limit);
-    canonicalize_core::<D>(&input_dataset, hndq_call_counter)
+    let input_dataset = normalize_dataset(&input_dataset, options);
+    let issued_identifiers_map = issue_normalized_map::<D>(&input_dataset, options)?;
+    let relabeled_dataset = relabel(&input_dataset, &issued_identifiers_map)?;
+    Ok(serialize(&relabeled_dataset))
 }
 
-/// Re-label blank node identifiers in the input dataset according to the issued identifiers map.
-/// Note that the output `Dataset` does not retain the order of quads, unlike `Vec<Quad>`.
+/// Given some options (e.g., call limit),
+/// returns the serialized canonical form of the canonicalized dataset like
+/// `canonicalize_quads_with`, together with the input quads that were dropped as duplicates
+/// of earlier ones, which a dataset cannot contain.
+/// A quad occurring `n` times is listed `n - 1` times, in the order of the input quads.
 ///
 /// # Examples
 ///
 /// ```
-/// use oxrdf::Dataset;
+/// use oxrdf::Quad;
 /// use oxttl::NQuadsParser;
-/// use rdf_canon::relabel;
-/// use std::collections::HashMap;
+/// use rdf_canon::{canonicalize_quads_checked, CanonicalizationOptions};
+/// use sha2::Sha256;
 /// use std::io::Cursor;
 ///
-/// let input = r#"
-/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
-/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
-/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
-/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
-/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
-/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#label> "one" .
+/// _:e0 <http://example.org/vocab#next> _:e1 .
 /// "#;
-/// let issued_identifiers_map = HashMap::from([
-///     ("g".to_string(), "c14n0".to_string()),
-///     ("e0".to_string(), "c14n1".to_string()),
-///     ("e1".to_string(), "c14n2".to_string()),
-///     ("e2".to_string(), "c14n3".to_string()),
-/// ]);
-/// let expected = r#"
-/// _:c14n1 <http://example.org/vocab#next> _:c14n2 _:c14n0 .
-/// _:c14n1 <http://example.org/vocab#prev> _:c14n3 _:c14n0 .
-/// _:c14n2 <http://example.org/vocab#next> _:c14n3 _:c14n0 .
-/// _:c14n2 <http://example.org/vocab#prev> _:c14n1 _:c14n0 .
-/// _:c14n3 <http://example.org/vocab#next> _:c14n1 _:c14n0 .
-/// _:c14n3 <http://example.org/vocab#prev> _:c14n2 _:c14n0 .
+/// let expected = r#"_:c14n0 <http://example.org/vocab#label> "one" .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
 /// "#;
 ///
-/// let input_quads = NQuadsParser::new()
+/// let input_quads: Vec<Quad> = NQuadsParser::new()
 ///     .for_reader(Cursor::new(input))
-///     .map(|x| x.unwrap());
-/// let input_dataset = Dataset::from_iter(input_quads);
-/// let labeled_dataset = relabel(&input_dataset, &issued_identifiers_map).unwrap();
-/// let expected_quads = NQuadsParser::new()
-///     .for_reader(Cursor::new(expected))
-///     .map(|x| x.unwrap());
-/// let expected_dataset = Dataset::from_iter(expected_quads);
+///     .map(|x| x.unwrap())
+///     .collect();
+/// let (canonicalized, duplicates) =
+///     canonicalize_quads_checked::<Sha256>(&input_quads, &CanonicalizationOptions::default())
+///         .unwrap();
 ///
-/// assert_eq!(labeled_dataset, expected_dataset);
+/// assert_eq!(canonicalized, expected);
+/// assert_eq!(duplicates, vec![input_quads[0].clone()]);
 /// ```
-pub fn relabel(
-    input_dataset: &Dataset,
-    issued_identifiers_map: &HashMap<String, String>,
-) -> Result<Dataset, CanonicalizationError> {
-    input_dataset
+pub fn canonicalize_quads_checked<D: Digest>(
+    input_quads: &[Quad],
+    options: &CanonicalizationOptions,
+) -> Result<(String, Vec<Quad>), CanonicalizationError> {
+    let mut seen = HashSet::with_capacity(input_quads.len());
+    let duplicates = input_quads
         .iter()
-        .map(|q| relabel_quad(q, issued_identifiers_map))
-        .collect()
+        .filter(|quad| !seen.insert(*quad))
+        .cloned()
+        .collect();
+    let canonicalized = canonicalize_quads_with::<D>(input_quads, options)?;
+    Ok((canonicalized, duplicates))
 }
 
-/// Re-label blank node identifiers in the input graph according to the issued identifiers map.
-/// Note that the output `Graph` does not retain the order of triples, unlike `Vec<Triple>`.
+/// Given some options (e.g., call limit),
+/// returns the serialized canonical form of the canonicalized graph,
+/// where any blank nodes in the input triples are assigned deterministic identifiers.
+/// Duplicate triples are canonicalized once, as in a graph.
 ///
 /// # Examples
 ///
 /// ```
-/// use oxrdf::Graph;
+/// use oxrdf::Triple;
 /// use oxttl::NTriplesParser;
-/// use rdf_canon::relabel_graph;
-/// use std::collections::HashMap;
+/// use rdf_canon::{canonicalize_triples_with, CanonicalizationOptions};
+/// use sha2::Sha256;
 /// use std::io::Cursor;
 ///
-/// let input = r#"
-/// _:e0 <http://example.org/vocab#next> _:e1 .
-/// _:e0 <http://example.org/vocab#prev> _:e2 .
-/// _:e1 <http://example.org/vocab#next> _:e2 .
-/// _:e1 <http://example.org/vocab#prev> _:e0 .
-/// _:e2 <http://example.org/vocab#next> _:e0 .
-/// _:e2 <http://example.org/vocab#prev> _:e1 .
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
 /// "#;
-/// let issued_identifiers_map = HashMap::from([
-///     ("e0".to_string(), "c14n0".to_string()),
-///     ("e1".to_string(), "c14n2".to_string()),
-///     ("e2".to_string(), "c14n1".to_string()),
-/// ]);
-/// let expected = r#"
-/// _:c14n0 <http://example.org/vocab#next> _:c14n2 .
-/// _:c14n0 <http://example.org/vocab#prev> _:c14n1 .
-/// _:c14n2 <http://example.org/vocab#next> _:c14n1 .
-/// _:c14n2 <http://example.org/vocab#prev> _:c14n0 .
+/// let expected = r#"_:c14n0 <http://example.org/vocab#next> _:c14n1 .
 /// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
-/// _:c14n1 <http://example.org/vocab#prev> _:c14n2 .
 /// "#;
 ///
-/// let input_triples = NTriplesParser::new()
+/// let input_triples: Vec<Triple> = NTriplesParser::new()
 ///     .for_reader(Cursor::new(input))
-///     .map(|x| x.unwrap());
-/// let input_graph = Graph::from_iter(input_triples);
-/// let labeled_graph = relabel_graph(&input_graph, &issued_identifiers_map).unwrap();
-/// let expected_triples = NTriplesParser::new()
-///     .for_reader(Cursor::new(expected))
-///     .map(|x| x.unwrap());
-/// let expected_graph = Graph::from_iter(expected_triples);
+///     .map(|x| x.unwrap())
+///     .collect();
+/// let options = CanonicalizationOptions {
+///     hndq_call_limit: Some(10000),
+///     ..Default::default()
+/// };
+/// let canonicalized = canonicalize_triples_with::<Sha256>(&input_triples, &options).unwrap();
 ///
-/// assert_eq!(labeled_graph, expected_graph);
+/// assert_eq!(canonicalized, expected);
 /// ```
-pub fn relabel_graph(
-    input_graph: &Graph,
-    issued_identifiers_map: &HashMap<String, String>,
-) -> Result<Graph, CanonicalizationError> {
-    input_graph
-        .iter()
-        .map(|t| relabel_triple(t, issued_identifiers_map))
-        .collect()
+pub fn canonicalize_triples_with<D: Digest>(
+    input_triples: &[Triple],
+    options: &CanonicalizationOptions,
+) -> Result<String, CanonicalizationError> {
+    let input_graph = Graph::from_iter(input_triples);
+    canonicalize_graph_with::<D>(&input_graph, options)
 }
 
-/// Re-label blank node identifiers in the input quads according to the issued identifiers map.
+/// Given some options (e.g., call limit),
+/// returns the exact byte sequence that is to be hashed when signing or
+/// verifying the input dataset, i.e., the UTF-8 encoded canonical N-Quads
+/// document without any additional separator or trailing data.
+/// This is the same as `canonicalize_to_bytes`, named for signing and verification code
+/// so that signers and verifiers agree on what is hashed.
 ///
 /// # Examples
 ///
 /// ```
-/// use oxrdf::Quad;
+/// use oxrdf::Dataset;
 /// use oxttl::NQuadsParser;
-/// use rdf_canon::relabel_quads;
-/// use std::collections::HashMap;
+/// use rdf_canon::{canonicalization_signing_base, CanonicalizationOptions};
+/// use sha2::{Digest, Sha256};
 /// use std::io::Cursor;
 ///
-/// let input = r#"
-/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
-/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
-/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
-/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
-/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
-/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// <urn:ex:s> <urn:ex:p> "signed" .
 /// "#;
-/// let issued_identifiers_map = HashMap::from([
-///     ("g".to_string(), "c14n0".to_string()),
-///     ("e0".to_string(), "c14n1".to_string()),
-///     ("e1".to_string(), "c14n2".to_string()),
-///     ("e2".to_string(), "c14n3".to_string()),
-/// ]);
-/// let expected = r#"
-/// _:c14n1 <http://example.org/vocab#next> _:c14n2 _:c14n0 .
-/// _:c14n1 <http://example.org/vocab#prev> _:c14n3 _:c14n0 .
-/// _:c14n2 <http://example.org/vocab#next> _:c14n3 _:c14n0 .
-/// _:c14n2 <http://example.org/vocab#prev> _:c14n1 _:c14n0 .
-/// _:c14n3 <http://example.org/vocab#next> _:c14n1 _:c14n0 .
-/// _:c14n3 <http://example.org/vocab#prev> _:c14n2 _:c14n0 .
+/// let expected = r#"<urn:ex:s> <urn:ex:p> "signed" .
+/// _:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
 /// "#;
 ///
-/// let input_quads: Vec<Quad> = NQuadsParser::new()
+/// let input_quads = NQuadsParser::new()
 ///     .for_reader(Cursor::new(input))
-///     .map(|x| x.unwrap())
-///     .collect();
-/// let labeled_quads = relabel_quads(&input_quads, &issued_identifiers_map).unwrap();
-/// let expected_quads: Vec<Quad> = NQuadsParser::new()
-///     .for_reader(Cursor::new(expected))
-///     .map(|x| x.unwrap())
-///     .collect();
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let options = CanonicalizationOptions::default();
+/// let signing_base =
+///     canonicalization_signing_base::<Sha256>(&input_dataset, &options).unwrap();
 ///
-/// assert_eq!(labeled_quads, expected_quads);
+/// assert_eq!(signing_base, expected.as_bytes());
+/// let _digest_to_sign = Sha256::digest(&signing_base);
 /// ```
-pub fn relabel_quads(
-    input_quads: &[Quad],
-    issued_identifiers_map: &HashMap<String, String>,
-) -> Result<Vec<Quad>, CanonicalizationError> {
-    input_quads
-        .iter()
-        .map(|q| relabel_quad(q.into(), issued_identifiers_map))
-        .collect()
+pub fn canonicalization_signing_base<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<Vec<u8>, CanonicalizationError> {
+    canonicalize_to_bytes::<D>(input_dataset, options)
 }
 
-fn relabel_quad(
-    q: QuadRef,
-    issued_identifiers_map: &HashMap<String, String>,
-) -> Result<Quad, CanonicalizationError> {
-    Ok(Quad::new(
-        relabel_subject(q.subject, issued_identifiers_map)?,
-        q.predicate,
-        relabel_term(q.object, issued_identifiers_map)?,
-        relabel_graph_name(q.graph_name, issued_identifiers_map)?,
-    ))
+/// Given some options (e.g., call limit),
+/// returns the digest of the serialized canonical form of the canonicalized dataset
+/// computed with the hash algorithm `D`, which is also used for canonicalizing.
+/// The statements are serialized and sorted in memory as in [`serialize_to_writer`] and then
+/// fed into the hash one at a time, which saves building the output as a single string.
+/// Under the default labeling, isomorphic datasets produce identical hashes, so the hash can
+/// serve as a content address of the dataset.
+/// This does not hold if the options change the labels depending on the input,
+/// as `PrefixCollisionPolicy::FreshPrefix` does, and hashes computed with different
+/// `canonical_prefix` or `label_formatter` options are not comparable with each other.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{canonicalize, dataset_hash, CanonicalizationOptions};
+/// use sha2::{Digest, Sha256};
+/// use std::io::Cursor;
+///
+/// let parse = |input: &str| {
+///     Dataset::from_iter(
+///         NQuadsParser::new()
+///             .for_reader(Cursor::new(input))
+///             .map(|x| x.unwrap()),
+///     )
+/// };
+/// let a = parse("_:e0 <http://example.org/vocab#next> _:e1 .\n");
+/// let b = parse("_:x <http://example.org/vocab#next> _:y .\n");
+/// let options = CanonicalizationOptions::default();
+///
+/// let hash = dataset_hash::<Sha256>(&a, &options).unwrap();
+/// assert_eq!(hash, dataset_hash::<Sha256>(&b, &options).unwrap());
+/// assert_eq!(hash, Sha256::digest(canonicalize(&a).unwrap()).to_vec());
+/// ```
+pub fn dataset_hash<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<Vec<u8>, CanonicalizationError> {
+    let relabeled_dataset = canonicalize_to_cow::<D>(input_dataset, options)?;
+    let mut writer = DigestWriter(D::new());
+    serialize_to_writer(&relabeled_dataset, &mut writer)?;
+    Ok(writer.0.finalize().to_vec())
 }
 
-fn relabel_triple(
-    t: TripleRef,
-    issued_identifiers_map: &HashMap<String, String>,
-) -> Result<Triple, CanonicalizationError> {
-    Ok(Triple::new(
-        relabel_subject(t.subject, issued_identifiers_map)?,
-        t.predicate,
-        relabel_term(t.object, issued_identifiers_map)?,
-    ))
+/// Feeds everything written into the hash.
+struct DigestWriter<D: Digest>(D);
+
+impl<D: Digest> Write for DigestWriter<D> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
-fn relabel_subject(
-    s: SubjectRef,
-    issued_identifiers_map: &HashMap<String, String>,
-) -> Result<Subject, CanonicalizationError> {
-    match s {
-        SubjectRef::BlankNode(blank_node) => {
-            match relabel_blank_node(blank_node, issued_identifiers_map) {
-                Ok(canonicalized_blank_node) => Ok(Subject::BlankNode(canonicalized_blank_node)),
-                Err(e) => Err(e),
-            }
+/// Returns the serialized canonical form of the canonicalized dataset split into
+/// the default graph, serialized as an N-Triples document,
+/// and the named graphs, serialized as an N-Quads document.
+/// Blank nodes are labeled over the whole input dataset,
+/// so that their labels are consistent across both documents.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::canonicalize_split;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 _:g .
+/// <urn:ex:s> <urn:ex:p> _:e1 .
+/// "#;
+/// let expected_default_graph = r#"<urn:ex:s> <urn:ex:p> _:c14n1 .
+/// _:c14n2 <http://example.org/vocab#next> _:c14n1 .
+/// "#;
+/// let expected_named_graphs = r#"_:c14n1 <http://example.org/vocab#next> _:c14n2 _:c14n0 .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let (default_graph, named_graphs) = canonicalize_split(&input_dataset).unwrap();
+///
+/// assert_eq!(default_graph, expected_default_graph);
+/// assert_eq!(named_graphs, expected_named_graphs);
+/// ```
+pub fn canonicalize_split(
+    input_dataset: &Dataset,
+) -> Result<(String, String), CanonicalizationError> {
+    let issued_identifiers_map = issue(input_dataset)?;
+    let relabeled_dataset = relabel(input_dataset, &issued_identifiers_map)?;
+    let (default_graph_quads, named_graph_quads): (Vec<_>, Vec<_>) = relabeled_dataset
+        .iter()
+        .partition(|q| q.graph_name.is_default_graph());
+    let default_graph = Graph::from_iter(
+        default_graph_quads
+            .into_iter()
+            .map(|q| TripleRef::new(q.subject, q.predicate, q.object)),
+    );
+    let named_graphs = Dataset::from_iter(named_graph_quads);
+    Ok((serialize_graph(&default_graph), serialize(&named_graphs)))
+}
+
+/// Given some options (e.g., call limit),
+/// returns the serialized canonical form of the union of the given graphs of the dataset,
+/// where `GraphNameRef::DefaultGraph` selects the default graph.
+/// The quads in the other graphs are ignored altogether, as if they were not in the dataset:
+/// a blank node appearing in both a given graph and another graph is labeled from its quads
+/// in the given graphs only, so its label does not depend on the other graphs and
+/// is the same as when canonicalizing a dataset consisting of the given graphs.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::{Dataset, GraphNameRef, NamedNodeRef};
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{canonicalize_graphs, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 <urn:ex:g1> .
+/// _:e1 <http://example.org/vocab#label> "one" <urn:ex:g1> .
+/// _:e0 <http://example.org/vocab#label> "private" <urn:ex:g2> .
+/// <urn:ex:s> <urn:ex:p> "default" .
+/// "#;
+/// let expected = r#"<urn:ex:s> <urn:ex:p> "default" .
+/// _:c14n0 <http://example.org/vocab#label> "one" <urn:ex:g1> .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 <urn:ex:g1> .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let graph_names = [
+///     GraphNameRef::DefaultGraph,
+///     NamedNodeRef::new("urn:ex:g1").unwrap().into(),
+/// ];
+/// let canonicalized = canonicalize_graphs::<Sha256>(
+///     &input_dataset,
+///     &graph_names,
+///     &CanonicalizationOptions::default(),
+/// )
+/// .unwrap();
+///
+/// assert_eq!(canonicalized, expected);
+/// ```
+pub fn canonicalize_graphs<D: Digest>(
+    input_dataset: &Dataset,
+    graph_names: &[GraphNameRef],
+    options: &CanonicalizationOptions,
+) -> Result<String, CanonicalizationError> {
+    let graph_names: HashSet<&GraphNameRef> = graph_names.iter().collect();
+    let selected_dataset = Dataset::from_iter(
+        input_dataset
+            .iter()
+            .filter(|q| graph_names.contains(&q.graph_name)),
+    );
+    canonicalize_with::<D>(&selected_dataset, options)
+}
+
+/// A canonical quad whose components are given in their canonical N-Quads forms,
+/// e.g., `<http://example.org/vocab#next>` or `_:c14n0`,
+/// for consumers that do not want to depend on oxrdf.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CanonQuad {
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+    /// `None` for the default graph
+    pub graph: Option<String>,
+}
+
+impl From<QuadRef<'_>> for CanonQuad {
+    fn from(quad: QuadRef<'_>) -> Self {
+        CanonQuad {
+            subject: quad.subject.to_string(),
+            predicate: quad.predicate.to_string(),
+            object: quad.object.to_string(),
+            graph: match quad.graph_name {
+                GraphNameRef::DefaultGraph => None,
+                graph_name => Some(graph_name.to_string()),
+            },
         }
-        _ => Ok(s.into()),
     }
 }
 
-fn relabel_term(
-    o: TermRef,
-    issued_identifiers_map: &HashMap<String, String>,
-) -> Result<Term, CanonicalizationError> {
-    match o {
-        TermRef::BlankNode(blank_node) => {
-            match relabel_blank_node(blank_node, issued_identifiers_map) {
-                Ok(canonicalized_blank_node) => Ok(Term::BlankNode(canonicalized_blank_node)),
-                Err(e) => Err(e),
-            }
-        }
-        _ => Ok(o.into()),
+/// Returns the canonicalized dataset as a list of [`CanonQuad`]s in canonical order,
+/// where any blank nodes in the input dataset are assigned deterministic identifiers.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{canonicalize_to_canon_quads, CanonQuad};
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e1 <http://example.org/vocab#label> "one" .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let canon_quads = canonicalize_to_canon_quads(&input_dataset).unwrap();
+///
+/// assert_eq!(
+///     canon_quads,
+///     vec![
+///         CanonQuad {
+///             subject: "_:c14n1".to_string(),
+///             predicate: "<http://example.org/vocab#label>".to_string(),
+///             object: "\"one\"".to_string(),
+///             graph: None,
+///         },
+///         CanonQuad {
+///             subject: "_:c14n2".to_string(),
+///             predicate: "<http://example.org/vocab#next>".to_string(),
+///             object: "_:c14n1".to_string(),
+///             graph: Some("_:c14n0".to_string()),
+///         },
+///     ]
+/// );
+/// ```
+pub fn canonicalize_to_canon_quads(
+    input_dataset: &Dataset,
+) -> Result<Vec<CanonQuad>, CanonicalizationError> {
+    let issued_identifiers_map = issue(input_dataset)?;
+    let relabeled_dataset = relabel(input_dataset, &issued_identifiers_map)?;
+    Ok(sort(&relabeled_dataset)
+        .iter()
+        .map(|q| q.as_ref().into())
+        .collect())
+}
+
+/// Returns the serialized canonical form of the canonicalized dataset as a reader,
+/// where any blank nodes in the input dataset are assigned deterministic identifiers.
+/// The dataset is canonicalized eagerly, but each quad is only serialized when the
+/// reader reaches it, so that the consumer pulls the output at its own pace.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{canonicalize, canonicalize_stream};
+/// use std::io::{BufRead, Cursor};
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// <urn:ex:s> <urn:ex:p> _:e1 .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let reader = canonicalize_stream(&input_dataset).unwrap();
+/// let lines: Vec<String> = reader.lines().map(|l| l.unwrap() + "\n").collect();
+///
+/// assert_eq!(lines.concat(), canonicalize(&input_dataset).unwrap());
+/// ```
+pub fn canonicalize_stream(input_dataset: &Dataset) -> Result<impl BufRead, CanonicalizationError> {
+    let issued_identifiers_map = issue(input_dataset)?;
+    let relabeled_dataset = relabel(input_dataset, &issued_identifiers_map)?;
+    Ok(CanonicalNQuadsReader {
+        quads: sort(&relabeled_dataset).into_iter(),
+        line: Vec::new(),
+        position: 0,
+    })
+}
+
+/// Given some options (e.g., call limit),
+/// writes the serialized canonical form of the canonicalized dataset to the writer
+/// and flushes the writer at the end.
+/// As with [`serialize_to_writer`], the statements are serialized and sorted in memory first
+/// and then written in one pass, which saves building the output as a single string.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{canonicalize, canonicalize_to_writer, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// _:e1 <http://example.org/vocab#label> "one" .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let options = CanonicalizationOptions::default();
+/// let mut output = Vec::new();
+/// canonicalize_to_writer::<Sha256, _>(&input_dataset, &options, &mut output).unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     canonicalize(&input_dataset).unwrap()
+/// );
+/// ```
+pub fn canonicalize_to_writer<D: Digest, W: Write>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+    mut writer: W,
+) -> Result<(), CanonicalizationError> {
+    let relabeled_dataset = canonicalize_to_dataset::<D>(input_dataset, options)?;
+    serialize_to_writer(&relabeled_dataset, &mut writer)?;
+    Ok(())
+}
+
+/// Returns the canonically relabeled dataset serialized as N-Quads,
+/// sorted by the given comparator instead of code point order,
+/// e.g., to group quads by predicate for presentation.
+/// Quads that compare equal are kept in code point order, so the output is stable.
+///
+/// Note that the output is **not** the canonical form of RDFC-1.0 unless the comparator
+/// reproduces code point order, so it must not be used for hashing or signing.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::canonicalize_sorted_by;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// _:e1 <http://example.org/vocab#label> "one" .
+/// "#;
+/// let expected = r#"_:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// _:c14n0 <http://example.org/vocab#label> "one" .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// // reverse order of predicates
+/// let sorted = canonicalize_sorted_by(&input_dataset, |a, b| {
+///     b.predicate.as_str().cmp(a.predicate.as_str())
+/// })
+/// .unwrap();
+///
+/// assert_eq!(sorted, expected);
+/// ```
+pub fn canonicalize_sorted_by<F>(
+    input_dataset: &Dataset,
+    compare: F,
+) -> Result<String, CanonicalizationError>
+where
+    F: Fn(&Quad, &Quad) -> Ordering,
+{
+    let issued_identifiers_map = issue(input_dataset)?;
+    let relabeled_dataset = relabel(input_dataset, &issued_identifiers_map)?;
+    let mut quads = sort(&relabeled_dataset);
+    // a stable sort keeps quads that compare equal in code point order
+    quads.sort_by(compare);
+    Ok(quads.iter().map(|q| q.to_string() + " .\n").collect())
+}
+
+/// A reader over the canonical n-quads forms of sorted quads, serializing one quad at a time
+struct CanonicalNQuadsReader {
+    quads: std::vec::IntoIter<Quad>,
+    line: Vec<u8>,
+    position: usize,
+}
+
+impl Read for CanonicalNQuadsReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
     }
 }
 
-fn relabel_graph_name(
-    g: GraphNameRef,
-    issued_identifiers_map: &HashMap<String, String>,
-) -> Result<GraphName, CanonicalizationError> {
-    match g {
-        GraphNameRef::BlankNode(blank_node) => {
-            match relabel_blank_node(blank_node, issued_identifiers_map) {
-                Ok(canonicalized_blank_node) => Ok(GraphName::BlankNode(canonicalized_blank_node)),
-                Err(e) => Err(e),
+impl BufRead for CanonicalNQuadsReader {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        if self.position >= self.line.len() {
+            if let Some(quad) = self.quads.next() {
+                self.line = (quad.to_string() + " .\n").into_bytes();
+                self.position = 0;
             }
         }
-        _ => Ok(g.into()),
+        Ok(&self.line[self.position..])
     }
-}
 
-fn relabel_blank_node(
-    b: BlankNodeRef,
-    issued_identifiers_map: &HashMap<String, String>,
-) -> Result<BlankNode, CanonicalizationError> {
-    let canonical_identifier = issued_identifiers_map.get(b.as_str());
-    match canonical_identifier {
-        Some(id) => Ok(BlankNode::new(id)?),
-        None => Err(CanonicalizationError::CanonicalIdentifierNotExist),
+    fn consume(&mut self, amt: usize) {
+        self.position = (self.position + amt).min(self.line.len());
     }
 }
 
-/// Sort each quad from the canonicalized dataset into code point order.
+/// Assigns deterministic identifiers to any blank nodes in the input dataset
+/// and returns the assignment result as a map.
 ///
 /// # Examples
 ///
 /// ```
-/// use oxrdf::{Dataset, Quad};
+/// use oxrdf::Dataset;
 /// use oxttl::NQuadsParser;
-/// use rdf_canon::{relabel, sort};
+/// use rdf_canon::issue;
 /// use std::collections::HashMap;
 /// use std::io::Cursor;
 ///
@@ -828,48 +1497,79 @@ fn relabel_blank_node(
 /// _:e2 <http://example.org/vocab#next> _:e0 _:g .
 /// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
 /// "#;
-/// let issued_identifiers_map = HashMap::from([
+/// let expected_map = HashMap::from([
 ///     ("g".to_string(), "c14n0".to_string()),
 ///     ("e0".to_string(), "c14n1".to_string()),
 ///     ("e1".to_string(), "c14n2".to_string()),
 ///     ("e2".to_string(), "c14n3".to_string()),
 /// ]);
-/// let expected = r#"
-/// _:c14n1 <http://example.org/vocab#next> _:c14n2 _:c14n0 .
-/// _:c14n1 <http://example.org/vocab#prev> _:c14n3 _:c14n0 .
-/// _:c14n2 <http://example.org/vocab#next> _:c14n3 _:c14n0 .
-/// _:c14n2 <http://example.org/vocab#prev> _:c14n1 _:c14n0 .
-/// _:c14n3 <http://example.org/vocab#next> _:c14n1 _:c14n0 .
-/// _:c14n3 <http://example.org/vocab#prev> _:c14n2 _:c14n0 .
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let issued_identifiers_map = issue(&input_dataset).unwrap();
+///
+/// assert_eq!(issued_identifiers_map, expected_map);
+/// ```
+pub fn issue(input_dataset: &Dataset) -> Result<HashMap<String, String>, CanonicalizationError> {
+    let options = CanonicalizationOptions::default();
+    issue_with::<Sha256>(input_dataset, &options)
+}
+
+/// Assigns deterministic identifiers to any blank nodes in the input dataset
+/// and returns the assignment result as a map in issuance order,
+/// i.e., the order of `c14n0`, `c14n1`, ...
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::issue_ordered;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
 /// "#;
 ///
 /// let input_quads = NQuadsParser::new()
 ///     .for_reader(Cursor::new(input))
 ///     .map(|x| x.unwrap());
 /// let input_dataset = Dataset::from_iter(input_quads);
-/// let labeled_dataset = relabel(&input_dataset, &issued_identifiers_map).unwrap();
-/// let canonicalized_quads = sort(&labeled_dataset);
-/// let expected_quads: Vec<Quad> = NQuadsParser::new()
-///     .for_reader(Cursor::new(expected))
-///     .map(|x| x.unwrap())
-///     .collect();
+/// let issued_identifiers_map = issue_ordered(&input_dataset).unwrap();
 ///
-/// assert_eq!(canonicalized_quads, expected_quads);
+/// assert_eq!(
+///     issued_identifiers_map.into_iter().collect::<Vec<_>>(),
+///     vec![
+///         ("g".to_string(), "c14n0".to_string()),
+///         ("e0".to_string(), "c14n1".to_string()),
+///         ("e1".to_string(), "c14n2".to_string()),
+///         ("e2".to_string(), "c14n3".to_string()),
+///     ]
+/// );
 /// ```
-pub fn sort(dataset: &Dataset) -> Vec<Quad> {
-    let mut ordered_dataset: Vec<QuadRef> = dataset.iter().collect();
-    ordered_dataset.sort_by_cached_key(|q| q.to_string());
-    ordered_dataset.iter().map(|q| q.into_owned()).collect()
+pub fn issue_ordered(
+    input_dataset: &Dataset,
+) -> Result<IndexMap<String, String>, CanonicalizationError> {
+    let options = CanonicalizationOptions::default();
+    issue_ordered_with::<Sha256>(input_dataset, &options)
 }
 
-/// Sort each triple from the canonicalized graph into code point order.
+/// Assigns deterministic identifiers to any blank nodes in the input graph
+/// and returns the assignment result as a map.
 ///
 /// # Examples
 ///
 /// ```
-/// use oxrdf::{Graph, Triple};
+/// use oxrdf::Graph;
 /// use oxttl::NTriplesParser;
-/// use rdf_canon::{relabel_graph, sort_graph};
+/// use rdf_canon::issue_graph;
 /// use std::collections::HashMap;
 /// use std::io::Cursor;
 ///
@@ -881,35 +1581,2942 @@ pub fn sort(dataset: &Dataset) -> Vec<Quad> {
 /// _:e2 <http://example.org/vocab#next> _:e0 .
 /// _:e2 <http://example.org/vocab#prev> _:e1 .
 /// "#;
-/// let issued_identifiers_map = HashMap::from([
+/// let expected_map = HashMap::from([
 ///     ("e0".to_string(), "c14n0".to_string()),
 ///     ("e1".to_string(), "c14n2".to_string()),
 ///     ("e2".to_string(), "c14n1".to_string()),
 /// ]);
-/// let expected = r#"
-/// _:c14n0 <http://example.org/vocab#next> _:c14n2 .
-/// _:c14n0 <http://example.org/vocab#prev> _:c14n1 .
-/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
-/// _:c14n1 <http://example.org/vocab#prev> _:c14n2 .
-/// _:c14n2 <http://example.org/vocab#next> _:c14n1 .
-/// _:c14n2 <http://example.org/vocab#prev> _:c14n0 .
-/// "#;
 ///
 /// let input_triples = NTriplesParser::new()
 ///     .for_reader(Cursor::new(input))
 ///     .map(|x| x.unwrap());
 /// let input_graph = Graph::from_iter(input_triples);
-/// let labeled_graph = relabel_graph(&input_graph, &issued_identifiers_map).unwrap();
-/// let canonicalized_triples = sort_graph(&labeled_graph);
-/// let expected_triples: Vec<Triple> = NTriplesParser::new()
-///     .for_reader(Cursor::new(expected))
+/// let issued_identifiers_map = issue_graph(&input_graph).unwrap();
+///
+/// assert_eq!(issued_identifiers_map, expected_map);
+/// ```
+pub fn issue_graph(input_graph: &Graph) -> Result<HashMap<String, String>, CanonicalizationError> {
+    let options = CanonicalizationOptions::default();
+    issue_graph_with::<Sha256>(input_graph, &options)
+}
+
+/// Assigns deterministic identifiers to any blank nodes in the input quads
+/// and returns the assignment result as a map.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Quad;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::issue_quads;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// "#;
+/// let expected_map = HashMap::from([
+///     ("g".to_string(), "c14n0".to_string()),
+///     ("e0".to_string(), "c14n1".to_string()),
+///     ("e1".to_string(), "c14n2".to_string()),
+///     ("e2".to_string(), "c14n3".to_string()),
+/// ]);
+///
+/// let input_quads: Vec<Quad> = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
 ///     .map(|x| x.unwrap())
 ///     .collect();
+/// let issued_identifiers_map = issue_quads(&input_quads).unwrap();
 ///
-/// assert_eq!(canonicalized_triples, expected_triples);
+/// assert_eq!(issued_identifiers_map, expected_map);
 /// ```
-pub fn sort_graph(graph: &Graph) -> Vec<Triple> {
-    let mut ordered_graph: Vec<TripleRef> = graph.iter().collect();
-    ordered_graph.sort_by_cached_key(|t| t.to_string());
-    ordered_graph.iter().map(|t| t.into_owned()).collect()
+pub fn issue_quads(input_quads: &[Quad]) -> Result<HashMap<String, String>, CanonicalizationError> {
+    let options = CanonicalizationOptions::default();
+    issue_quads_with::<Sha256>(input_quads, &options)
+}
+
+/// Assigns deterministic identifiers to any blank nodes in the input dataset
+/// and returns the assignment result as a lookup function,
+/// which maps an input blank node identifier to its canonical identifier.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::issue_lookup;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let lookup = issue_lookup(&input_dataset).unwrap();
+///
+/// assert_eq!(lookup("g"), Some("c14n0".to_string()));
+/// assert_eq!(lookup("e0"), Some("c14n1".to_string()));
+/// assert_eq!(lookup("x"), None);
+/// ```
+pub fn issue_lookup(
+    input_dataset: &Dataset,
+) -> Result<impl Fn(&str) -> Option<String>, CanonicalizationError> {
+    let issued_identifiers_map = issue(input_dataset)?;
+    Ok(move |identifier: &str| issued_identifiers_map.get(identifier).cloned())
+}
+
+/// Given some options (e.g., call limit),
+/// assigns deterministic identifiers to any blank nodes in the input dataset
+/// and returns the assignment result as a map.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{issue_with, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// "#;
+/// let expected_map = HashMap::from([
+///     ("g".to_string(), "c14n0".to_string()),
+///     ("e0".to_string(), "c14n1".to_string()),
+///     ("e1".to_string(), "c14n2".to_string()),
+///     ("e2".to_string(), "c14n3".to_string()),
+/// ]);
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let options = CanonicalizationOptions {
+///     hndq_call_limit: Some(10000),
+///     ..Default::default()
+/// };
+///
+/// let issued_identifiers_map = issue_with::<Sha256>(&input_dataset, &options).unwrap();
+///
+/// assert_eq!(issued_identifiers_map, expected_map);
+/// ```
+pub fn issue_with<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<HashMap<String, String>, CanonicalizationError> {
+    let (issued_identifiers_map, _) = issue_with_stats::<D>(input_dataset, options)?;
+    Ok(issued_identifiers_map)
+}
+
+/// Given some options (e.g., call limit),
+/// assigns deterministic identifiers to any blank nodes in the input dataset
+/// and returns the canonical issuer, whose issued identifiers map keeps the issuance order.
+/// The issuer can be used to relabel other quads referring to the same blank nodes,
+/// or to issue further identifiers following the canonical ones.
+/// Note that identifiers issued afterwards follow the canonical identifier prefix
+/// even if a label formatter is given in the options.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{issue_with_issuer, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e2 .
+/// _:e2 <http://example.org/vocab#next> _:e0 .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let options = CanonicalizationOptions::default();
+/// let mut issuer = issue_with_issuer::<Sha256>(&input_dataset, &options).unwrap();
+///
+/// let issuance_order: Vec<_> = issuer.issued_identifiers_map().values().collect();
+/// assert_eq!(issuance_order, vec!["c14n0", "c14n1", "c14n2"]);
+/// assert_eq!(issuer.get("e0"), Some("c14n0".to_string()));
+/// assert_eq!(issuer.issue("x"), "c14n3");
+/// ```
+pub fn issue_with_issuer<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<IdentifierIssuer, CanonicalizationError> {
+    let (canonical_issuer, _) =
+        issue_with_options::<D>(input_dataset, options, &mut Recording::default())?;
+    Ok(canonical_issuer)
+}
+
+/// Given some options (e.g., call limit),
+/// assigns deterministic identifiers to any blank nodes in the input dataset
+/// and returns the assignment result as a map in issuance order.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{issue_ordered_with, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::{io::Cursor, sync::Arc};
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#label> "one" .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let options = CanonicalizationOptions {
+///     label_formatter: Some(Arc::new(|n| format!("b{:04}", n))),
+///     ..Default::default()
+/// };
+/// let issued_identifiers_map = issue_ordered_with::<Sha256>(&input_dataset, &options).unwrap();
+///
+/// assert_eq!(
+///     issued_identifiers_map.values().collect::<Vec<_>>(),
+///     vec!["b0000", "b0001"]
+/// );
+/// assert_eq!(issued_identifiers_map["e1"], "b0000");
+/// ```
+pub fn issue_ordered_with<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<IndexMap<String, String>, CanonicalizationError> {
+    Ok(issue_with_issuer::<D>(input_dataset, options)?.into_issued_identifiers_map())
+}
+
+/// Given a hash algorithm chosen at runtime and some options (e.g., call limit),
+/// assigns deterministic identifiers to any blank nodes in the input dataset
+/// and returns the assignment result as a map.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{issue_with, issue_with_algorithm, CanonicalizationOptions, HashAlgorithm};
+/// use sha2::Sha512;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// _:e1 <http://example.org/vocab#label> "one" .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let options = CanonicalizationOptions::default();
+/// let issued_identifiers_map =
+///     issue_with_algorithm(&input_dataset, HashAlgorithm::Sha512, &options).unwrap();
+///
+/// assert_eq!(
+///     issued_identifiers_map,
+///     issue_with::<Sha512>(&input_dataset, &options).unwrap()
+/// );
+/// ```
+pub fn issue_with_algorithm(
+    input_dataset: &Dataset,
+    algorithm: HashAlgorithm,
+    options: &CanonicalizationOptions,
+) -> Result<HashMap<String, String>, CanonicalizationError> {
+    match algorithm {
+        HashAlgorithm::Sha256 => issue_with::<Sha256>(input_dataset, options),
+        HashAlgorithm::Sha384 => issue_with::<Sha384>(input_dataset, options),
+        HashAlgorithm::Sha512 => issue_with::<Sha512>(input_dataset, options),
+    }
+}
+
+/// Given a Hash N-Degree Quads call counter (e.g., `PerNodeHndqCallCounter`),
+/// assigns deterministic identifiers to any blank nodes in the input dataset
+/// and returns the assignment result as a map.
+/// The counter replaces the `SimpleHndqCallCounter` built from `hndq_call_limit`
+/// in the other functions, which allows budgeting the calls differently.
+/// Unlike the other `issue_*` functions, this takes no `CanonicalizationOptions`:
+/// the input is not normalized, `max_quads_per_blank_node` is not checked, the canonical
+/// identifiers always have the `c14n` prefix, and no progress is reported.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{
+///     counter::{HndqCallCounter, PerNodeHndqCallCounter},
+///     issue_with_counter,
+/// };
+/// use sha2::Sha256;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e2 .
+/// _:e2 <http://example.org/vocab#next> _:e0 .
+/// "#;
+/// let expected = HashMap::from([
+///     ("e0".to_string(), "c14n0".to_string()),
+///     ("e1".to_string(), "c14n1".to_string()),
+///     ("e2".to_string(), "c14n2".to_string()),
+/// ]);
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let counter = PerNodeHndqCallCounter::new(Some(10));
+/// let issued_identifiers_map = issue_with_counter::<Sha256, _>(&input_dataset, counter).unwrap();
+///
+/// assert_eq!(issued_identifiers_map, expected);
+/// ```
+pub fn issue_with_counter<D: Digest, C: HndqCallCounter + MaybeSend>(
+    input_dataset: &Dataset,
+    hndq_call_counter: C,
+) -> Result<HashMap<String, String>, CanonicalizationError> {
+    canonicalize_core::<D, C>(input_dataset, hndq_call_counter)
+}
+
+/// Given some options (e.g., call limit),
+/// assigns deterministic identifiers to any blank nodes in the input graph
+/// and returns the assignment result as a map.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Graph;
+/// use oxttl::NTriplesParser;
+/// use rdf_canon::{issue_graph_with, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e0 <http://example.org/vocab#prev> _:e2 .
+/// _:e1 <http://example.org/vocab#next> _:e2 .
+/// _:e1 <http://example.org/vocab#prev> _:e0 .
+/// _:e2 <http://example.org/vocab#next> _:e0 .
+/// _:e2 <http://example.org/vocab#prev> _:e1 .
+/// "#;
+/// let expected_map = HashMap::from([
+///     ("e0".to_string(), "c14n0".to_string()),
+///     ("e1".to_string(), "c14n2".to_string()),
+///     ("e2".to_string(), "c14n1".to_string()),
+/// ]);
+///
+/// let input_triples = NTriplesParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_graph = Graph::from_iter(input_triples);
+/// let options = CanonicalizationOptions {
+///     hndq_call_limit: Some(10000),
+///     ..Default::default()
+/// };
+///
+/// let issued_identifiers_map = issue_graph_with::<Sha256>(&input_graph, &options).unwrap();
+///
+/// assert_eq!(issued_identifiers_map, expected_map);
+/// ```
+pub fn issue_graph_with<D: Digest>(
+    input_graph: &Graph,
+    options: &CanonicalizationOptions,
+) -> Result<HashMap<String, String>, CanonicalizationError> {
+    let input_dataset = Dataset::from_iter(
+        input_graph
+            .iter()
+            .map(|t| QuadRef::new(t.subject, t.predicate, t.object, GraphNameRef::DefaultGraph)),
+    );
+    issue_with::<D>(&input_dataset, options)
+}
+
+/// Given some options (e.g., call limit),
+/// assigns deterministic identifiers to any blank nodes in the input quads
+/// and returns the assignment result as a map.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Quad;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{issue_quads_with, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// "#;
+/// let expected_map = HashMap::from([
+///     ("g".to_string(), "c14n0".to_string()),
+///     ("e0".to_string(), "c14n1".to_string()),
+///     ("e1".to_string(), "c14n2".to_string()),
+///     ("e2".to_string(), "c14n3".to_string()),
+/// ]);
+///
+/// let input_quads: Vec<Quad> = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap())
+///     .collect();
+/// let options = CanonicalizationOptions {
+///     hndq_call_limit: Some(10000),
+///     ..Default::default()
+/// };
+///
+/// let issued_identifiers_map = issue_quads_with::<Sha256>(&input_quads, &options).unwrap();
+///
+/// assert_eq!(issued_identifiers_map, expected_map);
+/// ```
+pub fn issue_quads_with<D: Digest>(
+    input_quads: &[Quad],
+    options: &CanonicalizationOptions,
+) -> Result<HashMap<String, String>, CanonicalizationError> {
+    let input_dataset = Dataset::from_iter(input_quads);
+    issue_with::<D>(&input_dataset, options)
+}
+
+/// Given some options (e.g., call limit),
+/// assigns deterministic identifiers to any blank nodes in the input dataset
+/// and returns the assignment result as a map,
+/// along with statistics such as the number of calls to the Hash N-Degree Quads algorithm.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{issue_with, issue_with_stats, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let options = CanonicalizationOptions::default();
+///
+/// let (issued_identifiers_map, stats) =
+///     issue_with_stats::<Sha256>(&input_dataset, &options).unwrap();
+///
+/// assert_eq!(
+///     issued_identifiers_map,
+///     issue_with::<Sha256>(&input_dataset, &options).unwrap()
+/// );
+/// assert_eq!(stats.blank_node_count, 4);
+/// assert!(stats.hndq_calls > 0);
+/// ```
+pub fn issue_with_stats<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<(HashMap<String, String>, CanonicalizationStats), CanonicalizationError> {
+    let (canonical_issuer, stats) =
+        issue_with_options::<D>(input_dataset, options, &mut Recording::default())?;
+    let issued_identifiers_map = canonical_issuer
+        .into_issued_identifiers_map()
+        .into_iter()
+        .collect();
+    Ok((issued_identifiers_map, stats))
+}
+
+/// Given some options (e.g., call limit),
+/// assigns deterministic identifiers to any blank nodes in the input graph
+/// and returns the assignment result as a map,
+/// along with statistics such as the number of calls to the Hash N-Degree Quads algorithm.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Graph;
+/// use oxttl::NTriplesParser;
+/// use rdf_canon::{issue_graph_with_stats, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// "#;
+///
+/// let input_triples = NTriplesParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_graph = Graph::from_iter(input_triples);
+/// let options = CanonicalizationOptions::default();
+///
+/// let (_, stats) = issue_graph_with_stats::<Sha256>(&input_graph, &options).unwrap();
+///
+/// assert_eq!(stats.blank_node_count, 2);
+/// ```
+pub fn issue_graph_with_stats<D: Digest>(
+    input_graph: &Graph,
+    options: &CanonicalizationOptions,
+) -> Result<(HashMap<String, String>, CanonicalizationStats), CanonicalizationError> {
+    let input_dataset = Dataset::from_iter(
+        input_graph
+            .iter()
+            .map(|t| QuadRef::new(t.subject, t.predicate, t.object, GraphNameRef::DefaultGraph)),
+    );
+    issue_with_stats::<D>(&input_dataset, options)
+}
+
+/// Given some options (e.g., call limit),
+/// assigns deterministic identifiers to any blank nodes in the input quads
+/// and returns the assignment result as a map,
+/// along with statistics such as the number of calls to the Hash N-Degree Quads algorithm.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Quad;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{issue_quads_with_stats, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e1 <http://example.org/vocab#next> _:e0 _:g .
+/// "#;
+///
+/// let input_quads: Vec<Quad> = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap())
+///     .collect();
+/// let options = CanonicalizationOptions::default();
+///
+/// let (_, stats) = issue_quads_with_stats::<Sha256>(&input_quads, &options).unwrap();
+///
+/// assert_eq!(stats.blank_node_count, 3);
+/// ```
+pub fn issue_quads_with_stats<D: Digest>(
+    input_quads: &[Quad],
+    options: &CanonicalizationOptions,
+) -> Result<(HashMap<String, String>, CanonicalizationStats), CanonicalizationError> {
+    let input_dataset = Dataset::from_iter(input_quads);
+    issue_with_stats::<D>(&input_dataset, options)
+}
+
+/// Given some options (e.g., call limit),
+/// assigns deterministic identifiers to any blank nodes in the input dataset
+/// and returns the assignment result as a map.
+/// If `transcript` is given, every input and output of the hash algorithm
+/// is appended to it in the order of computation, so that a verifier can
+/// replay the canonicalization step by step.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{issue_with_transcript, CanonicalizationOptions, TranscriptOperation};
+/// use sha2::{Digest, Sha256};
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let options = CanonicalizationOptions::default();
+/// let mut transcript = Vec::new();
+///
+/// issue_with_transcript::<Sha256>(&input_dataset, &options, Some(&mut transcript)).unwrap();
+///
+/// assert_eq!(transcript[0].operation, TranscriptOperation::HashFirstDegreeQuads);
+/// for entry in transcript {
+///     assert_eq!(base16ct::lower::encode_string(&Sha256::digest(&entry.input)), entry.output);
+/// }
+/// ```
+#[cfg(feature = "transcript")]
+pub fn issue_with_transcript<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+    transcript: Option<&mut Vec<TranscriptEntry>>,
+) -> Result<HashMap<String, String>, CanonicalizationError> {
+    let mut recording = Recording {
+        transcript: transcript.is_some().then(Vec::new),
+        ..Default::default()
+    };
+    let result = issue_with_options::<D>(input_dataset, options, &mut recording);
+
+    // keep the entries recorded so far even if the canonicalization failed
+    if let (Some(transcript), Some(recorded)) = (transcript, recording.transcript) {
+        transcript.extend(recorded);
+    }
+    let (canonical_issuer, _) = result?;
+    Ok(canonical_issuer
+        .into_issued_identifiers_map()
+        .into_iter()
+        .collect())
+}
+
+/// Runs the canonicalization algorithm on the input dataset as specified in the options,
+/// i.e., with the normalizations, limits, counter strategy, and progress callback applied,
+/// and returns the canonical issuer, whose identifiers are formatted as specified in the options,
+/// along with the statistics on the run.
+/// What is requested in `recording` is recorded even if the canonicalization fails.
+fn issue_with_options<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+    recording: &mut Recording,
+) -> Result<(IdentifierIssuer, CanonicalizationStats), CanonicalizationError> {
+    let input_dataset = normalize_dataset(input_dataset, options);
+    issue_normalized::<D>(&input_dataset, options, recording)
+}
+
+/// Issues the canonical identifiers as [`issue_with_options`] does
+/// for an input dataset that the normalizations specified in the options were already applied to,
+/// so that callers that keep the normalized dataset do not scan it again.
+fn issue_normalized<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+    recording: &mut Recording,
+) -> Result<(IdentifierIssuer, CanonicalizationStats), CanonicalizationError> {
+    check_quads_per_blank_node(input_dataset, options)?;
+    let prefix = canonical_identifier_prefix(input_dataset, options)?;
+    let limit = options.hndq_call_limit;
+    let no_progress = |_, _| {};
+    let progress = options.progress.as_deref().unwrap_or(&no_progress);
+    let (canonical_issuer, stats) = match options.counter_strategy {
+        CounterStrategy::Global => canonicalize_core_with_recording::<D, _>(
+            input_dataset,
+            SimpleHndqCallCounter::new(limit),
+            progress,
+            recording,
+        ),
+        CounterStrategy::PerNode => canonicalize_core_with_recording::<D, _>(
+            input_dataset,
+            PerNodeHndqCallCounter::new(limit),
+            progress,
+            recording,
+        ),
+    }
+    .map_err(|e| format_canonical_identifiers_in_error(e, &prefix, options))?;
+    let issued_identifiers_map = format_canonical_identifiers(
+        canonical_issuer.into_issued_identifiers_map(),
+        &prefix,
+        options,
+    )?;
+    let canonical_issuer =
+        IdentifierIssuer::with_issued_identifiers(&prefix, issued_identifiers_map);
+    Ok((canonical_issuer, stats))
+}
+
+/// Returns the issued identifiers map as `issue_with` does
+/// for an input dataset that the normalizations specified in the options were already applied to.
+fn issue_normalized_map<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<HashMap<String, String>, CanonicalizationError> {
+    let (canonical_issuer, _) =
+        issue_normalized::<D>(input_dataset, options, &mut Recording::default())?;
+    Ok(canonical_issuer
+        .into_issued_identifiers_map()
+        .into_iter()
+        .collect())
+}
+
+/// Given some options (e.g., call limit),
+/// assigns deterministic identifiers to any blank nodes in the input dataset
+/// and returns the assignment result as a map,
+/// along with the paths chosen by the Hash N-Degree Quads algorithm for each blank node
+/// it is run for by the canonicalization algorithm, in the order of processing.
+/// The traces of two datasets that are expected to be isomorphic can be compared to find
+/// where their canonicalizations diverge, as the chosen paths only consist of related hashes
+/// and temporary identifiers, which do not depend on the input blank node identifiers.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{issue_with, issue_with_chosen_paths, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::io::Cursor;
+///
+/// let parse = |input: &str| {
+///     Dataset::from_iter(
+///         NQuadsParser::new()
+///             .for_reader(Cursor::new(input.to_string()))
+///             .map(|x| x.unwrap()),
+///     )
+/// };
+/// let a = parse("_:e0 <http://example.org/vocab#next> _:e1 .\n_:e1 <http://example.org/vocab#next> _:e0 .\n");
+/// let b = parse("_:x <http://example.org/vocab#next> _:y .\n_:y <http://example.org/vocab#next> _:x .\n");
+/// let options = CanonicalizationOptions::default();
+///
+/// let (issued_identifiers_map, traces_a) =
+///     issue_with_chosen_paths::<Sha256>(&a, &options).unwrap();
+/// let (_, traces_b) = issue_with_chosen_paths::<Sha256>(&b, &options).unwrap();
+///
+/// assert_eq!(issued_identifiers_map, issue_with::<Sha256>(&a, &options).unwrap());
+/// assert_eq!(traces_a.len(), 2);
+/// assert_eq!(traces_a[0].identifier, "e0");
+/// // _:e1 is related to _:e0 both as a subject and as an object
+/// assert_eq!(traces_a[0].chosen_paths.len(), 2);
+/// for (trace_a, trace_b) in traces_a.iter().zip(&traces_b) {
+///     assert_eq!(trace_a.chosen_paths, trace_b.chosen_paths);
+///     assert_eq!(trace_a.hash, trace_b.hash);
+/// }
+/// ```
+pub fn issue_with_chosen_paths<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<(HashMap<String, String>, Vec<ChosenPathTrace>), CanonicalizationError> {
+    // the other fields depend on the enabled features
+    #[allow(clippy::needless_update)]
+    let mut recording = Recording {
+        chosen_path_traces: Some(Vec::new()),
+        ..Default::default()
+    };
+    let (canonical_issuer, _) = issue_with_options::<D>(input_dataset, options, &mut recording)?;
+    let issued_identifiers_map = canonical_issuer
+        .into_issued_identifiers_map()
+        .into_iter()
+        .collect();
+    Ok((
+        issued_identifiers_map,
+        recording.chosen_path_traces.unwrap_or_default(),
+    ))
+}
+
+/// Given some options (e.g., call limit),
+/// assigns deterministic identifiers to any blank nodes in the input dataset
+/// whose identifiers may not be valid N-Quads blank node labels,
+/// e.g., names taken from RDF/XML documents and kept with `BlankNode::new_unchecked`,
+/// and returns the assignment result as a map from the original identifiers.
+///
+/// The blank nodes are first relabeled with valid internal labels,
+/// which does not affect the canonical labeling,
+/// so the resulting map can be passed to `relabel` for the original dataset.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::{BlankNode, Dataset, GraphName, NamedNode, Quad};
+/// use rdf_canon::{issue_with_arbitrary_names, relabel, serialize, CanonicalizationOptions};
+/// use sha2::Sha256;
+///
+/// let next = NamedNode::new("http://example.org/vocab#next").unwrap();
+/// let a = BlankNode::new_unchecked("node A");
+/// let b = BlankNode::new_unchecked("node#B");
+/// let input_dataset = Dataset::from_iter([
+///     Quad::new(a.clone(), next.clone(), b.clone(), GraphName::DefaultGraph),
+///     Quad::new(b.clone(), next.clone(), a.clone(), GraphName::DefaultGraph),
+/// ]);
+/// let expected = r#"_:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#;
+///
+/// let options = CanonicalizationOptions::default();
+/// let issued_identifiers_map =
+///     issue_with_arbitrary_names::<Sha256>(&input_dataset, &options).unwrap();
+/// let labeled_dataset = relabel(&input_dataset, &issued_identifiers_map).unwrap();
+///
+/// assert!(issued_identifiers_map.contains_key("node A"));
+/// assert!(issued_identifiers_map.contains_key("node#B"));
+/// assert_eq!(serialize(&labeled_dataset), expected);
+/// ```
+pub fn issue_with_arbitrary_names<D: Digest>(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<HashMap<String, String>, CanonicalizationError> {
+    // map the original identifiers, in code point order, to b0, b1, ...
+    let internal_labels: HashMap<&str, BlankNode> = blank_node_identifiers(input_dataset)
+        .into_iter()
+        .enumerate()
+        .map(|(i, id)| (id, BlankNode::new_unchecked(format!("b{}", i))))
+        .collect();
+    let internal_dataset = input_dataset
+        .iter()
+        .map(|q| relabel_quad(q, &|b| Ok(internal_labels[b.as_str()].clone())))
+        .collect::<Result<Dataset, _>>()?;
+
+    let issued_identifiers_map = issue_with::<D>(&internal_dataset, options)?;
+    Ok(internal_labels
+        .into_iter()
+        .map(|(original, internal)| {
+            let canonical = issued_identifiers_map[internal.as_str()].clone();
+            (original.to_string(), canonical)
+        })
+        .collect())
+}
+
+/// Returns the input dataset with the normalizations specified in the options applied,
+/// borrowing it if nothing is to be changed.
+fn normalize_dataset<'a>(
+    input_dataset: &'a Dataset,
+    options: &CanonicalizationOptions,
+) -> Cow<'a, Dataset> {
+    if !normalizes_literals(options)
+        || !input_dataset
+            .iter()
+            .any(|q| normalize_literal(q.object, options).is_some())
+    {
+        return Cow::Borrowed(input_dataset);
+    }
+    Cow::Owned(Dataset::from_iter(input_dataset.iter().map(|q| {
+        let object = normalize_literal(q.object, options).unwrap_or_else(|| q.object.into_owned());
+        Quad::new(q.subject, q.predicate, object, q.graph_name)
+    })))
+}
+
+/// Returns the input graph with the normalizations specified in the options applied,
+/// borrowing it if nothing is to be changed.
+fn normalize_graph<'a>(
+    input_graph: &'a Graph,
+    options: &CanonicalizationOptions,
+) -> Cow<'a, Graph> {
+    if !normalizes_literals(options)
+        || !input_graph
+            .iter()
+            .any(|t| normalize_literal(t.object, options).is_some())
+    {
+        return Cow::Borrowed(input_graph);
+    }
+    Cow::Owned(Graph::from_iter(input_graph.iter().map(|t| {
+        let object = normalize_literal(t.object, options).unwrap_or_else(|| t.object.into_owned());
+        Triple::new(t.subject, t.predicate, object)
+    })))
+}
+
+/// Returns whether any of the normalizations of literals is enabled in the options.
+fn normalizes_literals(options: &CanonicalizationOptions) -> bool {
+    #[cfg(feature = "unicode-normalization")]
+    if options.unicode_normalization.is_some() {
+        return true;
+    }
+    options.normalize_language_tags
+}
+
+/// Returns the literal with the normalizations specified in the options applied
+/// if the term is a literal that they change.
+fn normalize_literal(term: TermRef, options: &CanonicalizationOptions) -> Option<Term> {
+    let TermRef::Literal(literal) = term else {
+        return None;
+    };
+    let value = Cow::Borrowed(literal.value());
+    let mut language = literal.language().map(Cow::Borrowed);
+    if options.normalize_language_tags {
+        if let Some(tag) = language
+            .as_mut()
+            .filter(|tag| tag.bytes().any(|b| b.is_ascii_uppercase()))
+        {
+            *tag = Cow::Owned(tag.to_ascii_lowercase());
+        }
+    }
+    #[cfg(feature = "unicode-normalization")]
+    let (value, language) = match options.unicode_normalization {
+        Some(form) => (
+            form.normalize(&value).map_or(value, Cow::Owned),
+            language.map(|tag| form.normalize(&tag).map_or(tag, Cow::Owned)),
+        ),
+        None => (value, language),
+    };
+    if matches!(value, Cow::Borrowed(_)) && !matches!(language, Some(Cow::Owned(_))) {
+        return None;
+    }
+    let literal = match language {
+        Some(language) => Literal::new_language_tagged_literal_unchecked(value, language),
+        None => Literal::new_typed_literal(value, literal.datatype()),
+    };
+    Some(literal.into())
+}
+
+/// Returns the prefix of the canonical identifiers to be issued for the input dataset,
+/// handling input blank node identifiers that collide with it as specified in the options.
+fn canonical_identifier_prefix(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<String, CanonicalizationError> {
+    let mut prefix = match &options.canonical_prefix {
+        Some(prefix) => {
+            let first_label = format!("{}0", prefix);
+            if BlankNode::new(first_label.as_str()).is_err() {
+                return Err(CanonicalizationError::InvalidCanonicalLabel(first_label));
+            }
+            prefix.clone()
+        }
+        None => CanonicalizationState::DEFAULT_CANONICAL_IDENTIFER_PREFIX.to_string(),
+    };
+    if options.prefix_collision == PrefixCollisionPolicy::Allow {
+        return Ok(prefix);
+    }
+
+    let collides = |identifier: &str, prefix: &str| {
+        identifier.strip_prefix(prefix).is_some_and(|counter| {
+            !counter.is_empty() && counter.bytes().all(|b| b.is_ascii_digit())
+        })
+    };
+    let identifiers = blank_node_identifiers(input_dataset);
+    while let Some(identifier) = identifiers.iter().find(|id| collides(id, &prefix)) {
+        match options.prefix_collision {
+            PrefixCollisionPolicy::Error => {
+                return Err(CanonicalizationError::PrefixCollision(
+                    identifier.to_string(),
+                ))
+            }
+            _ => prefix.push('_'),
+        }
+    }
+    Ok(prefix)
+}
+
+/// Checks that no blank node in the input dataset appears in more quads than
+/// `max_quads_per_blank_node` in the options, counting a quad once per occurrence
+/// of the blank node in it as the blank node to quads map does.
+/// If several blank nodes exceed the limit, the first one in code point order is reported.
+fn check_quads_per_blank_node(
+    input_dataset: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<(), CanonicalizationError> {
+    let Some(limit) = options.max_quads_per_blank_node else {
+        return Ok(());
+    };
+    let mut counts = BTreeMap::<&str, usize>::new();
+    for identifier in input_dataset
+        .iter()
+        .flat_map(blank_node_identifiers_in_quad)
+    {
+        *counts.entry(identifier).or_default() += 1;
+    }
+    match counts.into_iter().find(|(_, count)| *count > limit) {
+        Some((node, count)) => Err(CanonicalizationError::BlankNodeTooConnected {
+            node: node.to_string(),
+            count,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Formats the issued canonical identifiers with the label formatter in the options if given,
+/// or otherwise replaces their default prefix with the given one.
+fn format_canonical_identifiers<M>(
+    issued_identifiers_map: M,
+    prefix: &str,
+    options: &CanonicalizationOptions,
+) -> Result<M, CanonicalizationError>
+where
+    M: IntoIterator<Item = (String, String)> + FromIterator<(String, String)>,
+{
+    let default_prefix_len = CanonicalizationState::DEFAULT_CANONICAL_IDENTIFER_PREFIX.len();
+    let Some(label_formatter) = &options.label_formatter else {
+        if prefix == CanonicalizationState::DEFAULT_CANONICAL_IDENTIFER_PREFIX {
+            return Ok(issued_identifiers_map);
+        }
+        return Ok(issued_identifiers_map
+            .into_iter()
+            .map(|(k, v)| {
+                let counter = &v[default_prefix_len..];
+                (k, format!("{}{}", prefix, counter))
+            })
+            .collect());
+    };
+
+    let mut labels = HashSet::new();
+    issued_identifiers_map
+        .into_iter()
+        .map(|(k, v)| {
+            let counter = v[default_prefix_len..]
+                .parse()
+                .map_err(|_| CanonicalizationError::BlankNodeIdParseError(v.clone()))?;
+            let label = label_formatter(counter);
+            if BlankNode::new(label.as_str()).is_err() || !labels.insert(label.clone()) {
+                return Err(CanonicalizationError::InvalidCanonicalLabel(label));
+            }
+            Ok((k, label))
+        })
+        .collect()
+}
+
+/// Formats the canonical identifiers issued before the error occurred,
+/// if the error carries them.
+fn format_canonical_identifiers_in_error(
+    error: CanonicalizationError,
+    prefix: &str,
+    options: &CanonicalizationOptions,
+) -> CanonicalizationError {
+    match error {
+        CanonicalizationError::HndqCallLimitExceeded { limit, partial_map } => {
+            match format_canonical_identifiers(partial_map, prefix, options) {
+                Ok(partial_map) => {
+                    CanonicalizationError::HndqCallLimitExceeded { limit, partial_map }
+                }
+                Err(e) => e,
+            }
+        }
+        e => e,
+    }
+}
+
+/// Returns the distinct blank node identifiers in the input dataset.
+pub(crate) fn blank_node_identifiers(input_dataset: &Dataset) -> Vec<&str> {
+    let mut identifiers: Vec<&str> = input_dataset
+        .iter()
+        .flat_map(blank_node_identifiers_in_quad)
+        .collect();
+    identifiers.sort_unstable();
+    identifiers.dedup();
+    identifiers
+}
+
+/// Returns the identifiers of the blank nodes in the subject, object, and graph name
+/// of the quad, in this order, followed by those in quoted triples.
+pub(crate) fn blank_node_identifiers_in_quad(q: QuadRef<'_>) -> Vec<&str> {
+    let subject = match q.subject {
+        SubjectRef::BlankNode(b) => Some(b.as_str()),
+        _ => None,
+    };
+    let object = match q.object {
+        TermRef::BlankNode(b) => Some(b.as_str()),
+        _ => None,
+    };
+    let graph_name = match q.graph_name {
+        GraphNameRef::BlankNode(b) => Some(b.as_str()),
+        _ => None,
+    };
+    let identifiers = [subject, object, graph_name].into_iter().flatten();
+    #[cfg(feature = "rdf-star")]
+    let identifiers = identifiers.chain(quoted_blank_node_identifiers_in_quad(q));
+    identifiers.collect()
+}
+
+/// A map from blank node identifiers to the canonical identifiers issued for them,
+/// which the `relabel` functions accept, e.g., the `HashMap` returned by `issue` or
+/// the `IndexMap` in issuance order returned by `issue_ordered`.
+pub trait IssuedIdentifiersMap {
+    /// Returns the canonical identifier issued for the blank node identifier, if any.
+    fn canonical_identifier(&self, identifier: &str) -> Option<&str>;
+}
+
+impl<S: BuildHasher> IssuedIdentifiersMap for HashMap<String, String, S> {
+    fn canonical_identifier(&self, identifier: &str) -> Option<&str> {
+        self.get(identifier).map(String::as_str)
+    }
+}
+
+impl<S: BuildHasher> IssuedIdentifiersMap for IndexMap<String, String, S> {
+    fn canonical_identifier(&self, identifier: &str) -> Option<&str> {
+        self.get(identifier).map(String::as_str)
+    }
+}
+
+impl IssuedIdentifiersMap for BTreeMap<String, String> {
+    fn canonical_identifier(&self, identifier: &str) -> Option<&str> {
+        self.get(identifier).map(String::as_str)
+    }
+}
+
+/// Re-label blank node identifiers in the input dataset according to the issued identifiers map.
+/// Note that the output `Dataset` does not retain the order of quads, unlike `Vec<Quad>`;
+/// use `relabel_quads` to keep a given order of quads.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::relabel;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// "#;
+/// let issued_identifiers_map = HashMap::from([
+///     ("g".to_string(), "c14n0".to_string()),
+///     ("e0".to_string(), "c14n1".to_string()),
+///     ("e1".to_string(), "c14n2".to_string()),
+///     ("e2".to_string(), "c14n3".to_string()),
+/// ]);
+/// let expected = r#"
+/// _:c14n1 <http://example.org/vocab#next> _:c14n2 _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#prev> _:c14n3 _:c14n0 .
+/// _:c14n2 <http://example.org/vocab#next> _:c14n3 _:c14n0 .
+/// _:c14n2 <http://example.org/vocab#prev> _:c14n1 _:c14n0 .
+/// _:c14n3 <http://example.org/vocab#next> _:c14n1 _:c14n0 .
+/// _:c14n3 <http://example.org/vocab#prev> _:c14n2 _:c14n0 .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let labeled_dataset = relabel(&input_dataset, &issued_identifiers_map).unwrap();
+/// let expected_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(expected))
+///     .map(|x| x.unwrap());
+/// let expected_dataset = Dataset::from_iter(expected_quads);
+///
+/// assert_eq!(labeled_dataset, expected_dataset);
+/// ```
+pub fn relabel<M: IssuedIdentifiersMap + ?Sized>(
+    input_dataset: &Dataset,
+    issued_identifiers_map: &M,
+) -> Result<Dataset, CanonicalizationError> {
+    input_dataset
+        .iter()
+        .map(|q| relabel_quad(q, &|b| relabel_blank_node(b, issued_identifiers_map)))
+        .collect()
+}
+
+/// Re-label blank node identifiers in the input graph according to the issued identifiers map.
+/// Note that the output `Graph` does not retain the order of triples, unlike `Vec<Triple>`.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Graph;
+/// use oxttl::NTriplesParser;
+/// use rdf_canon::relabel_graph;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e0 <http://example.org/vocab#prev> _:e2 .
+/// _:e1 <http://example.org/vocab#next> _:e2 .
+/// _:e1 <http://example.org/vocab#prev> _:e0 .
+/// _:e2 <http://example.org/vocab#next> _:e0 .
+/// _:e2 <http://example.org/vocab#prev> _:e1 .
+/// "#;
+/// let issued_identifiers_map = HashMap::from([
+///     ("e0".to_string(), "c14n0".to_string()),
+///     ("e1".to_string(), "c14n2".to_string()),
+///     ("e2".to_string(), "c14n1".to_string()),
+/// ]);
+/// let expected = r#"
+/// _:c14n0 <http://example.org/vocab#next> _:c14n2 .
+/// _:c14n0 <http://example.org/vocab#prev> _:c14n1 .
+/// _:c14n2 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n2 <http://example.org/vocab#prev> _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#prev> _:c14n2 .
+/// "#;
+///
+/// let input_triples = NTriplesParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_graph = Graph::from_iter(input_triples);
+/// let labeled_graph = relabel_graph(&input_graph, &issued_identifiers_map).unwrap();
+/// let expected_triples = NTriplesParser::new()
+///     .for_reader(Cursor::new(expected))
+///     .map(|x| x.unwrap());
+/// let expected_graph = Graph::from_iter(expected_triples);
+///
+/// assert_eq!(labeled_graph, expected_graph);
+/// ```
+pub fn relabel_graph<M: IssuedIdentifiersMap + ?Sized>(
+    input_graph: &Graph,
+    issued_identifiers_map: &M,
+) -> Result<Graph, CanonicalizationError> {
+    input_graph
+        .iter()
+        .map(|t| relabel_triple(t, &|b| relabel_blank_node(b, issued_identifiers_map)))
+        .collect()
+}
+
+/// Re-label blank node identifiers in the input quads according to the issued identifiers map.
+/// Each output quad is the relabeled input quad at the same position,
+/// so the order of the input quads is kept, including any duplicates.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Quad;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::relabel_quads;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// "#;
+/// let issued_identifiers_map = HashMap::from([
+///     ("g".to_string(), "c14n0".to_string()),
+///     ("e0".to_string(), "c14n1".to_string()),
+///     ("e1".to_string(), "c14n2".to_string()),
+///     ("e2".to_string(), "c14n3".to_string()),
+/// ]);
+/// let expected = r#"
+/// _:c14n1 <http://example.org/vocab#next> _:c14n2 _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#prev> _:c14n3 _:c14n0 .
+/// _:c14n2 <http://example.org/vocab#next> _:c14n3 _:c14n0 .
+/// _:c14n2 <http://example.org/vocab#prev> _:c14n1 _:c14n0 .
+/// _:c14n3 <http://example.org/vocab#next> _:c14n1 _:c14n0 .
+/// _:c14n3 <http://example.org/vocab#prev> _:c14n2 _:c14n0 .
+/// "#;
+///
+/// let input_quads: Vec<Quad> = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap())
+///     .collect();
+/// let labeled_quads = relabel_quads(&input_quads, &issued_identifiers_map).unwrap();
+/// let expected_quads: Vec<Quad> = NQuadsParser::new()
+///     .for_reader(Cursor::new(expected))
+///     .map(|x| x.unwrap())
+///     .collect();
+///
+/// assert_eq!(labeled_quads, expected_quads);
+/// ```
+pub fn relabel_quads<M: IssuedIdentifiersMap + ?Sized>(
+    input_quads: &[Quad],
+    issued_identifiers_map: &M,
+) -> Result<Vec<Quad>, CanonicalizationError> {
+    input_quads
+        .iter()
+        .map(|q| relabel_quad(q.into(), &|b| relabel_blank_node(b, issued_identifiers_map)))
+        .collect()
+}
+
+/// Re-label blank node identifiers in the input triples according to the issued identifiers map.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Triple;
+/// use oxttl::NTriplesParser;
+/// use rdf_canon::relabel_triples;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// "#;
+/// let issued_identifiers_map = HashMap::from([
+///     ("e0".to_string(), "c14n1".to_string()),
+///     ("e1".to_string(), "c14n0".to_string()),
+/// ]);
+/// let expected = r#"
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// _:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// "#;
+///
+/// let input_triples: Vec<Triple> = NTriplesParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap())
+///     .collect();
+/// let labeled_triples = relabel_triples(&input_triples, &issued_identifiers_map).unwrap();
+/// let expected_triples: Vec<Triple> = NTriplesParser::new()
+///     .for_reader(Cursor::new(expected))
+///     .map(|x| x.unwrap())
+///     .collect();
+///
+/// assert_eq!(labeled_triples, expected_triples);
+/// ```
+pub fn relabel_triples<M: IssuedIdentifiersMap + ?Sized>(
+    input_triples: &[Triple],
+    issued_identifiers_map: &M,
+) -> Result<Vec<Triple>, CanonicalizationError> {
+    input_triples
+        .iter()
+        .map(|t| relabel_triple(t.into(), &|b| relabel_blank_node(b, issued_identifiers_map)))
+        .collect()
+}
+
+/// How `relabel_with_fallback` handles blank nodes that have no entry in the
+/// issued identifiers map, e.g., extra blank nodes in a superset of the canonicalized dataset.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum RelabelFallback {
+    /// Fail with `CanonicalizationError::CanonicalIdentifierNotExist`, as `relabel` does.
+    /// This is the default.
+    #[default]
+    Error,
+    /// Keep the original label, unless it equals one of the issued identifiers
+    /// in the output (e.g., `_:c14n0`), in which case `_` is appended until it is unique.
+    Keep,
+    /// Issue a fresh identifier with the given prefix, e.g., `extra0`, `extra1`, ...
+    /// in code point order of the original labels, skipping the issued identifiers.
+    Issue(String),
+}
+
+/// Re-label blank node identifiers in the input dataset according to the issued identifiers map,
+/// handling blank nodes that have no entry in the map as specified by `fallback`.
+/// The labels of such blank nodes never clash with the issued identifiers in the output.
+/// Note that the output `Dataset` does not retain the order of quads, unlike `Vec<Quad>`.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{relabel_with_fallback, serialize, CanonicalizationError, RelabelFallback};
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// _:c14n0 <http://example.org/vocab#extra> _:x .
+/// "#;
+/// let issued_identifiers_map = HashMap::from([
+///     ("e0".to_string(), "c14n0".to_string()),
+///     ("e1".to_string(), "c14n1".to_string()),
+/// ]);
+/// let kept = r#"_:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n0_ <http://example.org/vocab#extra> _:x .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#;
+/// let issued = r#"_:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// _:extra0 <http://example.org/vocab#extra> _:extra1 .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let relabel = |fallback| relabel_with_fallback(&input_dataset, &issued_identifiers_map, fallback);
+///
+/// assert!(matches!(
+///     relabel(RelabelFallback::Error),
+///     Err(CanonicalizationError::CanonicalIdentifierNotExist)
+/// ));
+/// assert_eq!(serialize(&relabel(RelabelFallback::Keep).unwrap()), kept);
+/// assert_eq!(
+///     serialize(&relabel(RelabelFallback::Issue("extra".to_string())).unwrap()),
+///     issued
+/// );
+/// ```
+pub fn relabel_with_fallback<M: IssuedIdentifiersMap + ?Sized>(
+    input_dataset: &Dataset,
+    issued_identifiers_map: &M,
+    fallback: RelabelFallback,
+) -> Result<Dataset, CanonicalizationError> {
+    let (mapped, unmapped): (Vec<&str>, Vec<&str>) = blank_node_identifiers(input_dataset)
+        .into_iter()
+        .partition(|id| issued_identifiers_map.canonical_identifier(id).is_some());
+    let issued: HashSet<&str> = mapped
+        .iter()
+        .filter_map(|id| issued_identifiers_map.canonical_identifier(id))
+        .collect();
+
+    let mut fallback_labels = HashMap::<&str, String>::new();
+    match fallback {
+        RelabelFallback::Error => return relabel(input_dataset, issued_identifiers_map),
+        RelabelFallback::Keep => {
+            let mut used: HashSet<String> = issued
+                .iter()
+                .chain(unmapped.iter())
+                .map(|id| id.to_string())
+                .collect();
+            for id in unmapped {
+                let mut label = id.to_string();
+                if issued.contains(id) {
+                    while used.contains(&label) {
+                        label.push('_');
+                    }
+                    used.insert(label.clone());
+                }
+                fallback_labels.insert(id, label);
+            }
+        }
+        RelabelFallback::Issue(prefix) => {
+            let mut counter = 0;
+            for id in unmapped {
+                let label = loop {
+                    let label = format!("{}{}", prefix, counter);
+                    counter += 1;
+                    if !issued.contains(label.as_str()) {
+                        break label;
+                    }
+                };
+                fallback_labels.insert(id, label);
+            }
+        }
+    }
+
+    let relabel_blank_node =
+        |b: BlankNodeRef| match issued_identifiers_map.canonical_identifier(b.as_str()) {
+            Some(id) => new_blank_node(id),
+            None => new_blank_node(&fallback_labels[b.as_str()]),
+        };
+    input_dataset
+        .iter()
+        .map(|q| relabel_quad(q, &relabel_blank_node))
+        .collect()
+}
+
+/// Re-label blank node identifiers in the input quads according to the issued identifiers map,
+/// leaving any blank node that has no entry in the map untouched.
+/// This allows applying the issued identifiers map of a whole dataset to
+/// an arbitrary subset of its quads, or to quads mixing in other blank nodes.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Quad;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::relabel_quads_lenient;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e0 <http://example.org/vocab#prev> _:x _:g .
+/// "#;
+/// let issued_identifiers_map = HashMap::from([
+///     ("g".to_string(), "c14n0".to_string()),
+///     ("e0".to_string(), "c14n1".to_string()),
+///     ("e1".to_string(), "c14n2".to_string()),
+///     ("e2".to_string(), "c14n3".to_string()),
+/// ]);
+/// let expected = r#"
+/// _:c14n1 <http://example.org/vocab#next> _:c14n2 _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#prev> _:x _:c14n0 .
+/// "#;
+///
+/// let input_quads: Vec<Quad> = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap())
+///     .collect();
+/// let labeled_quads = relabel_quads_lenient(&input_quads, &issued_identifiers_map).unwrap();
+/// let expected_quads: Vec<Quad> = NQuadsParser::new()
+///     .for_reader(Cursor::new(expected))
+///     .map(|x| x.unwrap())
+///     .collect();
+///
+/// assert_eq!(labeled_quads, expected_quads);
+/// ```
+pub fn relabel_quads_lenient<M: IssuedIdentifiersMap + ?Sized>(
+    input_quads: &[Quad],
+    issued_identifiers_map: &M,
+) -> Result<Vec<Quad>, CanonicalizationError> {
+    let relabel_blank_node =
+        |b: BlankNodeRef| match issued_identifiers_map.canonical_identifier(b.as_str()) {
+            Some(id) => new_blank_node(id),
+            None => Ok(b.into_owned()),
+        };
+    input_quads
+        .iter()
+        .map(|q| relabel_quad(q.into(), &relabel_blank_node))
+        .collect()
+}
+
+/// Re-label blank node identifiers in the input dataset according to the given function,
+/// which maps an input blank node identifier to its canonical identifier
+/// (e.g., the one returned by `issue_lookup`).
+/// Note that the output `Dataset` does not retain the order of quads, unlike `Vec<Quad>`.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{issue_lookup, relabel_with_fn, serialize};
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// <urn:ex:s> <urn:ex:p> _:e1 .
+/// "#;
+/// let expected = r#"<urn:ex:s> <urn:ex:p> _:c14n1 .
+/// _:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let lookup = issue_lookup(&input_dataset).unwrap();
+/// let labeled_dataset = relabel_with_fn(&input_dataset, lookup).unwrap();
+///
+/// assert_eq!(serialize(&labeled_dataset), expected);
+/// ```
+pub fn relabel_with_fn<F>(
+    input_dataset: &Dataset,
+    lookup: F,
+) -> Result<Dataset, CanonicalizationError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let relabel_blank_node = |b: BlankNodeRef| match lookup(b.as_str()) {
+        Some(id) => new_blank_node(&id),
+        None => Err(CanonicalizationError::CanonicalIdentifierNotExist),
+    };
+    input_dataset
+        .iter()
+        .map(|q| relabel_quad(q, &relabel_blank_node))
+        .collect()
+}
+
+/// Returns the keys of the issued identifiers map that do not correspond to any
+/// blank node in the input dataset, in code point order.
+/// This helps detect stale maps, e.g., persisted before some quads were removed,
+/// which `relabel` silently accepts.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::find_dangling_mappings;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// "#;
+/// let issued_identifiers_map = HashMap::from([
+///     ("e0".to_string(), "c14n0".to_string()),
+///     ("e1".to_string(), "c14n1".to_string()),
+///     ("e2".to_string(), "c14n2".to_string()),
+/// ]);
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+///
+/// assert_eq!(
+///     find_dangling_mappings(&input_dataset, &issued_identifiers_map),
+///     vec!["e2".to_string()]
+/// );
+/// ```
+pub fn find_dangling_mappings(
+    input_dataset: &Dataset,
+    issued_identifiers_map: &HashMap<String, String>,
+) -> Vec<String> {
+    let identifiers = blank_node_identifiers(input_dataset);
+    let mut dangling: Vec<String> = issued_identifiers_map
+        .keys()
+        .filter(|k| identifiers.binary_search(&k.as_str()).is_err())
+        .cloned()
+        .collect();
+    dangling.sort();
+    dangling
+}
+
+/// Returns whether the two input datasets are isomorphic,
+/// i.e., the same up to the renaming of blank nodes,
+/// by comparing the canonically relabeled quads without serializing them.
+/// Datasets with different numbers of quads or blank nodes, or different quads
+/// without blank nodes, are rejected before any hashing.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::is_isomorphic;
+/// use std::io::Cursor;
+///
+/// let a = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// "#;
+/// let b = r#"_:x <http://example.org/vocab#next> _:y .
+/// _:y <http://example.org/vocab#next> _:x .
+/// "#;
+/// let c = r#"_:x <http://example.org/vocab#next> _:y .
+/// _:y <http://example.org/vocab#next> _:y .
+/// "#;
+///
+/// let parse = |input: &str| {
+///     Dataset::from_iter(
+///         NQuadsParser::new()
+///             .for_reader(Cursor::new(input))
+///             .map(|x| x.unwrap()),
+///     )
+/// };
+///
+/// assert!(is_isomorphic(&parse(a), &parse(b)).unwrap());
+/// assert!(!is_isomorphic(&parse(a), &parse(c)).unwrap());
+/// ```
+pub fn is_isomorphic(a: &Dataset, b: &Dataset) -> Result<bool, CanonicalizationError> {
+    let options = CanonicalizationOptions::default();
+    is_isomorphic_with::<Sha256>(a, b, &options)
+}
+
+/// Given some options (e.g., call limit),
+/// returns whether the two input datasets are isomorphic, as [`is_isomorphic`] does.
+/// The datasets are normalized according to the options before comparing them.
+/// Only the limits and the normalizations apply; both datasets are labeled with the
+/// default canonical identifiers, ignoring `prefix_collision`, `canonical_prefix`,
+/// `label_formatter`, and `progress`.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{is_isomorphic_with, CanonicalizationOptions};
+/// use sha2::Sha384;
+/// use std::io::Cursor;
+///
+/// let a = r#"_:e0 <http://example.org/vocab#label> "one"@EN .
+/// "#;
+/// let b = r#"_:x <http://example.org/vocab#label> "one"@en .
+/// "#;
+///
+/// let parse = |input: &str| {
+///     Dataset::from_iter(
+///         NQuadsParser::new()
+///             .for_reader(Cursor::new(input))
+///             .map(|x| x.unwrap()),
+///     )
+/// };
+/// let options = CanonicalizationOptions {
+///     normalize_language_tags: true,
+///     ..Default::default()
+/// };
+///
+/// assert!(is_isomorphic_with::<Sha384>(&parse(a), &parse(b), &options).unwrap());
+/// ```
+pub fn is_isomorphic_with<D: Digest>(
+    a: &Dataset,
+    b: &Dataset,
+    options: &CanonicalizationOptions,
+) -> Result<bool, CanonicalizationError> {
+    let a = normalize_dataset(a, options);
+    let b = normalize_dataset(b, options);
+    if a.len() != b.len() || blank_node_identifiers(&a).len() != blank_node_identifiers(&b).len() {
+        return Ok(false);
+    }
+    let ground_quads = |input_dataset: &Dataset| {
+        input_dataset
+            .iter()
+            .filter(|q| blank_node_identifiers_in_quad(*q).is_empty())
+            .map(QuadRef::into_owned)
+            .collect::<HashSet<Quad>>()
+    };
+    if ground_quads(&a) != ground_quads(&b) {
+        return Ok(false);
+    }
+    // the labels depend on the input identifiers under, e.g., `PrefixCollisionPolicy::FreshPrefix`
+    let labeling_options = CanonicalizationOptions {
+        hndq_call_limit: options.hndq_call_limit,
+        max_quads_per_blank_node: options.max_quads_per_blank_node,
+        counter_strategy: options.counter_strategy,
+        normalize_language_tags: options.normalize_language_tags,
+        #[cfg(feature = "unicode-normalization")]
+        unicode_normalization: options.unicode_normalization,
+        ..Default::default()
+    };
+    let canonical_quads = |input_dataset: &Dataset| {
+        let issued_identifiers_map = issue_normalized_map::<D>(input_dataset, &labeling_options)?;
+        input_dataset
+            .iter()
+            .map(|q| relabel_quad(q, &|b| relabel_blank_node(b, &issued_identifiers_map)))
+            .collect::<Result<HashSet<Quad>, CanonicalizationError>>()
+    };
+    Ok(canonical_quads(&a)? == canonical_quads(&b)?)
+}
+
+/// Returns whether the two input graphs are isomorphic,
+/// i.e., the same up to the renaming of blank nodes.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Graph;
+/// use oxttl::NTriplesParser;
+/// use rdf_canon::is_isomorphic_graph;
+/// use std::io::Cursor;
+///
+/// let a = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// "#;
+/// let b = r#"_:x <http://example.org/vocab#next> _:y .
+/// _:y <http://example.org/vocab#next> _:x .
+/// "#;
+///
+/// let parse = |input: &str| {
+///     Graph::from_iter(
+///         NTriplesParser::new()
+///             .for_reader(Cursor::new(input))
+///             .map(|x| x.unwrap()),
+///     )
+/// };
+///
+/// assert!(is_isomorphic_graph(&parse(a), &parse(b)).unwrap());
+/// ```
+pub fn is_isomorphic_graph(a: &Graph, b: &Graph) -> Result<bool, CanonicalizationError> {
+    let options = CanonicalizationOptions::default();
+    is_isomorphic_graph_with::<Sha256>(a, b, &options)
+}
+
+/// Given some options (e.g., call limit),
+/// returns whether the two input graphs are isomorphic, as [`is_isomorphic_graph`] does.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Graph;
+/// use oxttl::NTriplesParser;
+/// use rdf_canon::{is_isomorphic_graph_with, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::io::Cursor;
+///
+/// let a = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// "#;
+/// let b = r#"_:x <http://example.org/vocab#next> _:y .
+/// _:y <http://example.org/vocab#next> _:y .
+/// "#;
+///
+/// let parse = |input: &str| {
+///     Graph::from_iter(
+///         NTriplesParser::new()
+///             .for_reader(Cursor::new(input))
+///             .map(|x| x.unwrap()),
+///     )
+/// };
+/// let options = CanonicalizationOptions::default();
+///
+/// assert!(!is_isomorphic_graph_with::<Sha256>(&parse(a), &parse(b), &options).unwrap());
+/// ```
+pub fn is_isomorphic_graph_with<D: Digest>(
+    a: &Graph,
+    b: &Graph,
+    options: &CanonicalizationOptions,
+) -> Result<bool, CanonicalizationError> {
+    let into_dataset =
+        |input_graph: &Graph| {
+            Dataset::from_iter(input_graph.iter().map(|t| {
+                QuadRef::new(t.subject, t.predicate, t.object, GraphNameRef::DefaultGraph)
+            }))
+        };
+    is_isomorphic_with::<D>(&into_dataset(a), &into_dataset(b), options)
+}
+
+/// A canonicalized dataset along with a lookup from each canonical identifier
+/// to the blank node it was issued for.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExplorableCanonicalization {
+    /// The canonicalized dataset, where blank nodes have canonical identifiers
+    pub dataset: Dataset,
+    /// Each canonical identifier (e.g., `c14n0`), mapped to the identifier of the blank node
+    /// in the input dataset and the canonicalized quads that the blank node appears in,
+    /// in canonical order
+    pub by_canonical: BTreeMap<String, (String, Vec<Quad>)>,
+}
+
+/// Returns the canonicalized dataset along with a lookup from each canonical identifier
+/// to the original blank node identifier and the quads that the blank node appears in.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::canonicalize_explorable;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#label> "one" .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let explorable = canonicalize_explorable(&input_dataset).unwrap();
+///
+/// let (original, quads) = &explorable.by_canonical["c14n0"];
+/// assert_eq!(original, "e1");
+/// assert_eq!(
+///     quads.iter().map(|q| q.to_string()).collect::<Vec<_>>(),
+///     vec![
+///         r#"_:c14n0 <http://example.org/vocab#label> "one""#,
+///         "_:c14n1 <http://example.org/vocab#next> _:c14n0",
+///     ]
+/// );
+/// assert_eq!(explorable.dataset.len(), 2);
+/// ```
+pub fn canonicalize_explorable(
+    input_dataset: &Dataset,
+) -> Result<ExplorableCanonicalization, CanonicalizationError> {
+    let issued_identifiers_map = issue(input_dataset)?;
+    let dataset = relabel(input_dataset, &issued_identifiers_map)?;
+
+    let mut by_canonical: BTreeMap<String, (String, Vec<Quad>)> = issued_identifiers_map
+        .into_iter()
+        .map(|(original, canonical)| (canonical, (original, Vec::new())))
+        .collect();
+    let mut ordered_quads: Vec<QuadRef> = dataset.iter().collect();
+    ordered_quads.sort_by_cached_key(|q| q.to_string());
+    for quad in ordered_quads {
+        let mut identifiers = blank_node_identifiers_in_quad(quad);
+        identifiers.sort_unstable();
+        identifiers.dedup();
+        for identifier in identifiers {
+            if let Some((_, quads)) = by_canonical.get_mut(identifier) {
+                quads.push(quad.into_owned());
+            }
+        }
+    }
+
+    Ok(ExplorableCanonicalization {
+        dataset,
+        by_canonical,
+    })
+}
+
+fn relabel_quad<F>(q: QuadRef, relabel_blank_node: &F) -> Result<Quad, CanonicalizationError>
+where
+    F: Fn(BlankNodeRef) -> Result<BlankNode, CanonicalizationError>,
+{
+    Ok(Quad::new(
+        relabel_subject(q.subject, relabel_blank_node)?,
+        q.predicate,
+        relabel_term(q.object, relabel_blank_node)?,
+        relabel_graph_name(q.graph_name, relabel_blank_node)?,
+    ))
+}
+
+fn relabel_triple<F>(t: TripleRef, relabel_blank_node: &F) -> Result<Triple, CanonicalizationError>
+where
+    F: Fn(BlankNodeRef) -> Result<BlankNode, CanonicalizationError>,
+{
+    Ok(Triple::new(
+        relabel_subject(t.subject, relabel_blank_node)?,
+        t.predicate,
+        relabel_term(t.object, relabel_blank_node)?,
+    ))
+}
+
+fn relabel_subject<F>(
+    s: SubjectRef,
+    relabel_blank_node: &F,
+) -> Result<Subject, CanonicalizationError>
+where
+    F: Fn(BlankNodeRef) -> Result<BlankNode, CanonicalizationError>,
+{
+    match s {
+        SubjectRef::BlankNode(blank_node) => match relabel_blank_node(blank_node) {
+            Ok(canonicalized_blank_node) => Ok(Subject::BlankNode(canonicalized_blank_node)),
+            Err(e) => Err(e),
+        },
+        #[cfg(feature = "rdf-star")]
+        SubjectRef::Triple(triple) => Ok(Subject::Triple(Box::new(relabel_triple(
+            triple.as_ref(),
+            relabel_blank_node,
+        )?))),
+        _ => Ok(s.into()),
+    }
+}
+
+fn relabel_term<F>(o: TermRef, relabel_blank_node: &F) -> Result<Term, CanonicalizationError>
+where
+    F: Fn(BlankNodeRef) -> Result<BlankNode, CanonicalizationError>,
+{
+    match o {
+        TermRef::BlankNode(blank_node) => match relabel_blank_node(blank_node) {
+            Ok(canonicalized_blank_node) => Ok(Term::BlankNode(canonicalized_blank_node)),
+            Err(e) => Err(e),
+        },
+        #[cfg(feature = "rdf-star")]
+        TermRef::Triple(triple) => Ok(Term::Triple(Box::new(relabel_triple(
+            triple.as_ref(),
+            relabel_blank_node,
+        )?))),
+        _ => Ok(o.into()),
+    }
+}
+
+fn relabel_graph_name<F>(
+    g: GraphNameRef,
+    relabel_blank_node: &F,
+) -> Result<GraphName, CanonicalizationError>
+where
+    F: Fn(BlankNodeRef) -> Result<BlankNode, CanonicalizationError>,
+{
+    match g {
+        GraphNameRef::BlankNode(blank_node) => match relabel_blank_node(blank_node) {
+            Ok(canonicalized_blank_node) => Ok(GraphName::BlankNode(canonicalized_blank_node)),
+            Err(e) => Err(e),
+        },
+        _ => Ok(g.into()),
+    }
+}
+
+fn relabel_blank_node<M: IssuedIdentifiersMap + ?Sized>(
+    b: BlankNodeRef,
+    issued_identifiers_map: &M,
+) -> Result<BlankNode, CanonicalizationError> {
+    let canonical_identifier = issued_identifiers_map.canonical_identifier(b.as_str());
+    match canonical_identifier {
+        Some(id) => new_blank_node(id),
+        None => Err(CanonicalizationError::CanonicalIdentifierNotExist),
+    }
+}
+
+/// Returns the blank node with the identifier, or an error holding the identifier if invalid.
+fn new_blank_node(id: &str) -> Result<BlankNode, CanonicalizationError> {
+    BlankNode::new(id).map_err(|_| CanonicalizationError::BlankNodeIdParseError(id.to_string()))
+}
+
+/// Sort each quad from the canonicalized dataset into code point order.
+///
+/// Quads are compared by their canonical N-Quads forms, and two quads with the same
+/// form are identical, so this is a total order and the result does not depend on
+/// the iteration order of the dataset. The underlying sort is stable as well.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::{Dataset, Quad};
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{relabel, sort};
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// "#;
+/// let issued_identifiers_map = HashMap::from([
+///     ("g".to_string(), "c14n0".to_string()),
+///     ("e0".to_string(), "c14n1".to_string()),
+///     ("e1".to_string(), "c14n2".to_string()),
+///     ("e2".to_string(), "c14n3".to_string()),
+/// ]);
+/// let expected = r#"
+/// _:c14n1 <http://example.org/vocab#next> _:c14n2 _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#prev> _:c14n3 _:c14n0 .
+/// _:c14n2 <http://example.org/vocab#next> _:c14n3 _:c14n0 .
+/// _:c14n2 <http://example.org/vocab#prev> _:c14n1 _:c14n0 .
+/// _:c14n3 <http://example.org/vocab#next> _:c14n1 _:c14n0 .
+/// _:c14n3 <http://example.org/vocab#prev> _:c14n2 _:c14n0 .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let labeled_dataset = relabel(&input_dataset, &issued_identifiers_map).unwrap();
+/// let canonicalized_quads = sort(&labeled_dataset);
+/// let expected_quads: Vec<Quad> = NQuadsParser::new()
+///     .for_reader(Cursor::new(expected))
+///     .map(|x| x.unwrap())
+///     .collect();
+///
+/// assert_eq!(canonicalized_quads, expected_quads);
+/// ```
+pub fn sort(dataset: &Dataset) -> Vec<Quad> {
+    let mut ordered_dataset: Vec<QuadRef> = dataset.iter().collect();
+    ordered_dataset.sort_by_cached_key(|q| q.to_string());
+    ordered_dataset.iter().map(|q| q.into_owned()).collect()
+}
+
+/// Sort each triple from the canonicalized graph into code point order.
+///
+/// As with [`sort`], the result does not depend on the iteration order of the graph.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::{Graph, Triple};
+/// use oxttl::NTriplesParser;
+/// use rdf_canon::{relabel_graph, sort_graph};
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e0 <http://example.org/vocab#prev> _:e2 .
+/// _:e1 <http://example.org/vocab#next> _:e2 .
+/// _:e1 <http://example.org/vocab#prev> _:e0 .
+/// _:e2 <http://example.org/vocab#next> _:e0 .
+/// _:e2 <http://example.org/vocab#prev> _:e1 .
+/// "#;
+/// let issued_identifiers_map = HashMap::from([
+///     ("e0".to_string(), "c14n0".to_string()),
+///     ("e1".to_string(), "c14n2".to_string()),
+///     ("e2".to_string(), "c14n1".to_string()),
+/// ]);
+/// let expected = r#"
+/// _:c14n0 <http://example.org/vocab#next> _:c14n2 .
+/// _:c14n0 <http://example.org/vocab#prev> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// _:c14n1 <http://example.org/vocab#prev> _:c14n2 .
+/// _:c14n2 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n2 <http://example.org/vocab#prev> _:c14n0 .
+/// "#;
+///
+/// let input_triples = NTriplesParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_graph = Graph::from_iter(input_triples);
+/// let labeled_graph = relabel_graph(&input_graph, &issued_identifiers_map).unwrap();
+/// let canonicalized_triples = sort_graph(&labeled_graph);
+/// let expected_triples: Vec<Triple> = NTriplesParser::new()
+///     .for_reader(Cursor::new(expected))
+///     .map(|x| x.unwrap())
+///     .collect();
+///
+/// assert_eq!(canonicalized_triples, expected_triples);
+/// ```
+pub fn sort_graph(graph: &Graph) -> Vec<Triple> {
+    let mut ordered_graph: Vec<TripleRef> = graph.iter().collect();
+    ordered_graph.sort_by_cached_key(|t| t.to_string());
+    ordered_graph.iter().map(|t| t.into_owned()).collect()
+}
+
+/// Sorts the triples in the canonical order, i.e., the code point order of
+/// their N-Triples serializations, keeping any duplicates.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Triple;
+/// use oxttl::NTriplesParser;
+/// use rdf_canon::sort_triples;
+/// use std::io::Cursor;
+///
+/// let input = r#"
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// _:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// "#;
+/// let expected = r#"
+/// _:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#;
+///
+/// let input_triples: Vec<Triple> = NTriplesParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap())
+///     .collect();
+/// let expected_triples: Vec<Triple> = NTriplesParser::new()
+///     .for_reader(Cursor::new(expected))
+///     .map(|x| x.unwrap())
+///     .collect();
+///
+/// assert_eq!(sort_triples(&input_triples), expected_triples);
+/// ```
+pub fn sort_triples(triples: &[Triple]) -> Vec<Triple> {
+    let mut ordered_triples = triples.to_vec();
+    ordered_triples.sort_by_cached_key(|t| t.to_string());
+    ordered_triples
+}
+
+/// The distinct predicates, literal datatypes, and language tags used in a dataset.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TermInventory {
+    pub predicates: BTreeSet<NamedNode>,
+    /// Note that simple literals have the datatype `xsd:string` and
+    /// language-tagged strings have the datatype `rdf:langString`.
+    pub datatypes: BTreeSet<NamedNode>,
+    pub languages: BTreeSet<String>,
+}
+
+/// Collects the distinct predicates, literal datatypes, and language tags
+/// used in the input dataset in a single pass.
+/// The result does not depend on blank node labels, so it is the same for
+/// the input dataset and its canonicalized form.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::{Dataset, NamedNode};
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::term_inventory;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e0 <http://example.org/vocab#label> "zero"@en .
+/// _:e1 <http://example.org/vocab#value> "1"^^<http://www.w3.org/2001/XMLSchema#integer> .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let inventory = term_inventory(&input_dataset);
+///
+/// assert_eq!(inventory.predicates.len(), 3);
+/// assert!(inventory
+///     .datatypes
+///     .contains(&NamedNode::new_unchecked("http://www.w3.org/2001/XMLSchema#integer")));
+/// assert!(inventory.languages.contains("en"));
+/// ```
+pub fn term_inventory(input_dataset: &Dataset) -> TermInventory {
+    let mut inventory = TermInventory::default();
+    for quad in input_dataset.iter() {
+        inventory.predicates.insert(quad.predicate.into_owned());
+        if let TermRef::Literal(literal) = quad.object {
+            inventory.datatypes.insert(literal.datatype().into_owned());
+            if let Some(language) = literal.language() {
+                inventory.languages.insert(language.to_string());
+            }
+        }
+    }
+    inventory
+}
+
+/// Given some options (e.g., call limit), canonicalizes the input dataset and returns
+/// whether its serialized canonical form equals `expected`, e.g., the expected output
+/// of a test in the RDFC-1.0 test suite.
+/// Trailing newlines are ignored on both sides, so that a missing final newline
+/// in `expected` does not cause a mismatch.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{verify_canonical, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#label> "one" .
+/// "#;
+/// let expected = r#"_:c14n0 <http://example.org/vocab#label> "one" .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 ."#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let options = CanonicalizationOptions::default();
+///
+/// assert!(verify_canonical::<Sha256>(&input_dataset, expected, &options).unwrap());
+/// assert!(!verify_canonical::<Sha256>(&input_dataset, input, &options).unwrap());
+/// ```
+pub fn verify_canonical<D: Digest>(
+    input_dataset: &Dataset,
+    expected: &str,
+    options: &CanonicalizationOptions,
+) -> Result<bool, CanonicalizationError> {
+    let canonicalized = canonicalize_with::<D>(input_dataset, options)?;
+    Ok(canonicalized.trim_end_matches('\n') == expected.trim_end_matches('\n'))
+}
+
+/// Checks a canonicalization result claimed by a third party, i.e., that relabeling
+/// the original dataset with the claimed issued identifiers map reproduces the claimed
+/// serialized canonical form, and that the map is the one the canonicalization algorithm
+/// issues for the original dataset.
+/// Returns `Ok(false)` if either check fails, including if the map lacks a blank node
+/// or holds an invalid label, and an error only if canonicalizing the original dataset fails.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::verify_relabeling;
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#label> "one" .
+/// "#;
+/// let claimed_canonical = r#"_:c14n0 <http://example.org/vocab#label> "one" .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#;
+/// let claimed_map = HashMap::from([
+///     ("e0".to_string(), "c14n1".to_string()),
+///     ("e1".to_string(), "c14n0".to_string()),
+/// ]);
+/// // consistent with the claimed canonical form, but not what the algorithm issues
+/// let swapped_map = HashMap::from([
+///     ("e0".to_string(), "c14n0".to_string()),
+///     ("e1".to_string(), "c14n1".to_string()),
+/// ]);
+/// let swapped_canonical = r#"_:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#label> "one" .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+///
+/// assert!(verify_relabeling(&input_dataset, &claimed_map, claimed_canonical).unwrap());
+/// assert!(!verify_relabeling(&input_dataset, &claimed_map, swapped_canonical).unwrap());
+/// assert!(!verify_relabeling(&input_dataset, &swapped_map, swapped_canonical).unwrap());
+/// ```
+pub fn verify_relabeling(
+    original: &Dataset,
+    claimed_map: &HashMap<String, String>,
+    claimed_canonical: &str,
+) -> Result<bool, CanonicalizationError> {
+    // consistency: the map relabels the original dataset into the claimed canonical form
+    let Ok(relabeled_dataset) = relabel(original, claimed_map) else {
+        return Ok(false);
+    };
+    if serialize(&relabeled_dataset) != claimed_canonical {
+        return Ok(false);
+    }
+
+    // correctness: the map is the one issued by the canonicalization algorithm
+    Ok(issue(original)? == *claimed_map)
+}
+
+/// Returns an N-Quads parser for the entry points taking text,
+/// which also accepts quoted triples if the `rdf-star` feature is enabled.
+pub(crate) fn nquads_parser() -> NQuadsParser {
+    let parser = NQuadsParser::new();
+    #[cfg(feature = "rdf-star")]
+    let parser = parser.with_quoted_triples();
+    parser
+}
+
+/// Returns a TriG parser as [`nquads_parser`] does.
+fn trig_parser() -> TriGParser {
+    let parser = TriGParser::new();
+    #[cfg(feature = "rdf-star")]
+    let parser = parser.with_quoted_triples();
+    parser
+}
+
+/// Returns an N-Triples parser as [`nquads_parser`] does.
+fn ntriples_parser() -> NTriplesParser {
+    let parser = NTriplesParser::new();
+    #[cfg(feature = "rdf-star")]
+    let parser = parser.with_quoted_triples();
+    parser
+}
+
+/// Returns a Turtle parser as [`nquads_parser`] does.
+fn turtle_parser() -> TurtleParser {
+    let parser = TurtleParser::new();
+    #[cfg(feature = "rdf-star")]
+    let parser = parser.with_quoted_triples();
+    parser
+}
+
+/// Checks whether the N-Quads document read from `reader` is in canonical form,
+/// i.e., it is the serialized canonical form of the dataset it represents,
+/// without holding the whole document in memory.
+///
+/// Each line is checked to be a quad in canonical n-quads form terminated by a
+/// line feed, and the lines are checked to be in strictly increasing code point
+/// order as they are read. Only the quads containing blank nodes are retained,
+/// since the canonical labeling depends on them alone; they are canonicalized at
+/// the end to check that every blank node already has its canonical label.
+///
+/// Returns `Ok(false)` for documents that are not in canonical form, including
+/// syntactically invalid ones, and an error only if reading or canonicalizing fails.
+///
+/// # Examples
+///
+/// ```
+/// use rdf_canon::verify_canonical_stream;
+/// use std::io::Cursor;
+///
+/// let canonical = r#"<urn:ex:s> <urn:ex:p> _:c14n1 .
+/// _:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#;
+/// let relabeled = r#"<urn:ex:s> <urn:ex:p> _:c14n0 .
+/// _:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#;
+/// let unsorted = r#"_:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// <urn:ex:s> <urn:ex:p> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#;
+///
+/// assert!(verify_canonical_stream(Cursor::new(canonical)).unwrap());
+/// assert!(!verify_canonical_stream(Cursor::new(relabeled)).unwrap());
+/// assert!(!verify_canonical_stream(Cursor::new(unsorted)).unwrap());
+/// ```
+pub fn verify_canonical_stream<R: Read>(reader: R) -> Result<bool, CanonicalizationError> {
+    let mut reader = BufReader::new(reader);
+    let mut previous_line = String::new();
+    let mut line = String::new();
+    let mut quads_with_blank_nodes = Dataset::new();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+
+        // each line must be a single quad in canonical n-quads form
+        let Some(statement) = line.strip_suffix(" .\n") else {
+            return Ok(false);
+        };
+        let mut parsed = nquads_parser().for_slice(line.as_bytes());
+        let quad = match (parsed.next(), parsed.next()) {
+            (Some(Ok(quad)), None) => quad,
+            _ => return Ok(false),
+        };
+        if quad.to_string() != statement {
+            return Ok(false);
+        }
+
+        // lines must be in strictly increasing code point order
+        if !previous_line.is_empty() && line <= previous_line {
+            return Ok(false);
+        }
+        std::mem::swap(&mut line, &mut previous_line);
+
+        if !blank_node_identifiers_in_quad(quad.as_ref()).is_empty() {
+            quads_with_blank_nodes.insert(&quad);
+        }
+    }
+
+    // every blank node must be labeled with its canonical identifier
+    let issued_identifiers_map = issue(&quads_with_blank_nodes)?;
+    Ok(issued_identifiers_map.iter().all(|(k, v)| k == v))
+}
+
+/// Given some options (e.g., call limit),
+/// parses N-Quads from the reader and returns the serialized canonical form of the dataset.
+/// Malformed input results in `CanonicalizationError::Parse` with the location of the error,
+/// and empty input results in an empty string.
+///
+/// # Examples
+///
+/// ```
+/// use rdf_canon::{canonicalize_from_reader, CanonicalizationError, CanonicalizationOptions};
+/// use sha2::Sha256;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#label> "one" .
+/// "#;
+/// let expected = r#"_:c14n0 <http://example.org/vocab#label> "one" .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#;
+/// let options = CanonicalizationOptions::default();
+///
+/// let canonicalized =
+///     canonicalize_from_reader::<_, Sha256>(Cursor::new(input), &options).unwrap();
+/// assert_eq!(canonicalized, expected);
+///
+/// let empty = canonicalize_from_reader::<_, Sha256>(Cursor::new(""), &options).unwrap();
+/// assert_eq!(empty, "");
+///
+/// let malformed = "_:e0 <http://example.org/vocab#next> .\n";
+/// let error = canonicalize_from_reader::<_, Sha256>(Cursor::new(malformed), &options);
+/// assert!(matches!(error, Err(CanonicalizationError::Parse { line: 1, .. })));
+/// ```
+pub fn canonicalize_from_reader<R: Read, D: Digest>(
+    reader: R,
+    options: &CanonicalizationOptions,
+) -> Result<String, CanonicalizationError> {
+    let input_dataset = nquads_parser()
+        .for_reader(reader)
+        .collect::<Result<Dataset, _>>()?;
+    canonicalize_with::<D>(&input_dataset, options)
+}
+
+/// Parses the input as N-Quads and returns its serialized canonical form,
+/// borrowing the input instead of allocating a copy of it if it is already canonical,
+/// e.g., when re-canonicalizing a document for verification.
+/// The input is canonicalized in full either way, so this only saves the final allocation.
+///
+/// # Examples
+///
+/// ```
+/// use rdf_canon::{canonicalize_cow, CanonicalizationError};
+/// use std::borrow::Cow;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#label> "one" .
+/// "#;
+/// let expected = r#"_:c14n0 <http://example.org/vocab#label> "one" .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#;
+///
+/// let canonicalized = canonicalize_cow(input).unwrap();
+/// assert!(matches!(canonicalized, Cow::Owned(_)));
+/// assert_eq!(canonicalized, expected);
+///
+/// let recanonicalized = canonicalize_cow(expected).unwrap();
+/// assert!(matches!(recanonicalized, Cow::Borrowed(_)));
+/// assert_eq!(recanonicalized, expected);
+///
+/// let malformed = "_:e0 <http://example.org/vocab#next> .\n";
+/// assert!(matches!(
+///     canonicalize_cow(malformed),
+///     Err(CanonicalizationError::Parse { line: 1, .. })
+/// ));
+/// ```
+pub fn canonicalize_cow(input: &str) -> Result<Cow<'_, str>, CanonicalizationError> {
+    let input_dataset = nquads_parser()
+        .for_slice(input.as_bytes())
+        .collect::<Result<Dataset, _>>()?;
+    let canonicalized = canonicalize(&input_dataset)?;
+    if canonicalized == input {
+        Ok(Cow::Borrowed(input))
+    } else {
+        Ok(Cow::Owned(canonicalized))
+    }
+}
+
+/// Splits the input into N-Quads documents separated by `delimiter`, e.g., a file of many
+/// concatenated documents, and returns the serialized canonical form of each document,
+/// canonicalized independently, or the error that occurred for it.
+/// A malformed document does not affect the others, and the results are in the order of
+/// the documents, including an empty one for each empty document, e.g., after a trailing
+/// delimiter. An empty delimiter makes the whole input a single document.
+///
+/// # Examples
+///
+/// ```
+/// use rdf_canon::{canonicalize_documents, CanonicalizationError};
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// ---
+/// _:e0 <http://example.org/vocab#next> .
+/// ---
+/// _:x <http://example.org/vocab#label> "one" .
+/// "#;
+///
+/// let results = canonicalize_documents(input, "---\n");
+/// assert_eq!(results.len(), 3);
+/// assert_eq!(
+///     results[0].as_ref().unwrap(),
+///     "_:c14n0 <http://example.org/vocab#next> _:c14n1 .\n"
+/// );
+/// assert!(matches!(
+///     results[1],
+///     Err(CanonicalizationError::Parse { line: 1, .. })
+/// ));
+/// assert_eq!(
+///     results[2].as_ref().unwrap(),
+///     "_:c14n0 <http://example.org/vocab#label> \"one\" .\n"
+/// );
+/// ```
+pub fn canonicalize_documents(
+    input: &str,
+    delimiter: &str,
+) -> Vec<Result<String, CanonicalizationError>> {
+    let canonicalize_document = |document: &str| {
+        let input_dataset = nquads_parser()
+            .for_slice(document.as_bytes())
+            .collect::<Result<Dataset, _>>()?;
+        canonicalize(&input_dataset)
+    };
+    if delimiter.is_empty() {
+        return vec![canonicalize_document(input)];
+    }
+    input.split(delimiter).map(canonicalize_document).collect()
+}
+
+/// Returns a name-based (version 5) UUID for the input dataset,
+/// derived from the namespace and the serialized canonical form of the dataset.
+/// Isomorphic datasets therefore get the same UUID, which can serve as a fixed-width
+/// identifier of the dataset, e.g., as a database key.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::canonical_uuid;
+/// use std::io::Cursor;
+/// use uuid::Uuid;
+///
+/// let parse = |input: &str| {
+///     Dataset::from_iter(
+///         NQuadsParser::new()
+///             .for_reader(Cursor::new(input))
+///             .map(|x| x.unwrap()),
+///     )
+/// };
+/// let a = parse("_:e0 <http://example.org/vocab#next> _:e1 .\n");
+/// let b = parse("_:x <http://example.org/vocab#next> _:y .\n");
+///
+/// let uuid = canonical_uuid(&a, Uuid::NAMESPACE_URL).unwrap();
+/// assert_eq!(uuid, canonical_uuid(&b, Uuid::NAMESPACE_URL).unwrap());
+/// assert_eq!(uuid.get_version_num(), 5);
+/// assert_ne!(uuid, canonical_uuid(&a, Uuid::NAMESPACE_OID).unwrap());
+/// ```
+#[cfg(feature = "uuid")]
+pub fn canonical_uuid(
+    input_dataset: &Dataset,
+    namespace: uuid::Uuid,
+) -> Result<uuid::Uuid, CanonicalizationError> {
+    let canonicalized = canonicalize(input_dataset)?;
+    Ok(uuid::Uuid::new_v5(&namespace, canonicalized.as_bytes()))
+}
+
+/// Parses the input in the RDF syntax given by the media type,
+/// e.g., the `Content-Type` of an HTTP request, and returns its serialized canonical form.
+/// `application/n-quads`, `application/trig`, `application/n-triples`, and `text/turtle`
+/// are supported, ignoring case and any parameters such as `charset`.
+/// Other media types result in `CanonicalizationError::UnsupportedMediaType`.
+///
+/// # Examples
+///
+/// ```
+/// use rdf_canon::{canonicalize_media_type, CanonicalizationError};
+///
+/// let input = br#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#label> "one" .
+/// "#;
+/// let expected = r#"_:c14n0 <http://example.org/vocab#label> "one" .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#;
+///
+/// assert_eq!(
+///     canonicalize_media_type(input, "text/turtle; charset=utf-8").unwrap(),
+///     expected
+/// );
+/// assert!(matches!(
+///     canonicalize_media_type(input, "application/ld+json"),
+///     Err(CanonicalizationError::UnsupportedMediaType(_))
+/// ));
+/// ```
+pub fn canonicalize_media_type(
+    input: &[u8],
+    content_type: &str,
+) -> Result<String, CanonicalizationError> {
+    let media_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let input_dataset: Dataset = match media_type.as_str() {
+        "application/n-quads" => nquads_parser().for_slice(input).collect::<Result<_, _>>()?,
+        "application/trig" => trig_parser().for_slice(input).collect::<Result<_, _>>()?,
+        "application/n-triples" => ntriples_parser()
+            .for_slice(input)
+            .map(|t| t.map(|t| t.in_graph(GraphName::DefaultGraph)))
+            .collect::<Result<_, _>>()?,
+        "text/turtle" => turtle_parser()
+            .for_slice(input)
+            .map(|t| t.map(|t| t.in_graph(GraphName::DefaultGraph)))
+            .collect::<Result<_, _>>()?,
+        _ => {
+            return Err(CanonicalizationError::UnsupportedMediaType(
+                content_type.to_string(),
+            ))
+        }
+    };
+    canonicalize(&input_dataset)
+}
+
+/// The result of canonicalizing the same dataset with two hash algorithms,
+/// comparing the canonical identifiers issued to each blank node.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashAlgorithmComparison {
+    /// The blank nodes whose canonical identifiers differ,
+    /// mapped to the identifiers issued with the first and second algorithms respectively
+    pub differences: BTreeMap<String, (String, String)>,
+}
+
+impl HashAlgorithmComparison {
+    /// Returns true if both hash algorithms produce the same canonical labeling.
+    pub fn is_same_labeling(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// Canonicalizes the input dataset with the hash algorithms `A` and `B`,
+/// and returns which blank nodes are issued different canonical identifiers.
+/// Since the canonical identifiers are issued in the order of the hashes,
+/// switching the hash algorithm generally renumbers the labels, although the
+/// canonicalized datasets are isomorphic.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::compare_hash_algorithms;
+/// use sha2::{Sha256, Sha384};
+/// use std::collections::BTreeMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#p> "a" .
+/// _:e1 <http://example.org/vocab#p> "b" .
+/// "#;
+/// let expected_differences = BTreeMap::from([
+///     ("e0".to_string(), ("c14n0".to_string(), "c14n1".to_string())),
+///     ("e1".to_string(), ("c14n1".to_string(), "c14n0".to_string())),
+/// ]);
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+///
+/// let same = compare_hash_algorithms::<Sha256, Sha256>(&input_dataset).unwrap();
+/// assert!(same.is_same_labeling());
+///
+/// let comparison = compare_hash_algorithms::<Sha256, Sha384>(&input_dataset).unwrap();
+/// assert!(!comparison.is_same_labeling());
+/// assert_eq!(comparison.differences, expected_differences);
+/// ```
+pub fn compare_hash_algorithms<A: Digest, B: Digest>(
+    input_dataset: &Dataset,
+) -> Result<HashAlgorithmComparison, CanonicalizationError> {
+    let options = CanonicalizationOptions::default();
+    let first = issue_with::<A>(input_dataset, &options)?;
+    let second = issue_with::<B>(input_dataset, &options)?;
+    // both algorithms label the same blank nodes
+    let differences = first
+        .into_iter()
+        .filter(|(k, v)| second[k] != *v)
+        .map(|(k, v)| {
+            let w = second[&k].clone();
+            (k, (v, w))
+        })
+        .collect();
+    Ok(HashAlgorithmComparison { differences })
+}
+
+/// Checks whether the input dataset is already a normalized dataset,
+/// i.e., its blank nodes are labeled `c14n0`, `c14n1`, ... without gaps and
+/// each of them is labeled with the canonical identifier it would be issued.
+///
+/// Unlike `verify_canonical_stream`, which checks a serialized document,
+/// this checks the in-memory dataset, which has no order of quads.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::is_normalized_dataset;
+/// use std::io::Cursor;
+///
+/// let parse = |input: &str| {
+///     let input_quads = NQuadsParser::new()
+///         .for_reader(Cursor::new(input.to_string()))
+///         .map(|x| x.unwrap());
+///     Dataset::from_iter(input_quads)
+/// };
+///
+/// let normalized = parse(
+///     r#"<urn:ex:s> <urn:ex:p> _:c14n1 .
+/// _:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#,
+/// );
+/// let swapped = parse(
+///     r#"<urn:ex:s> <urn:ex:p> _:c14n0 .
+/// _:c14n0 <http://example.org/vocab#next> _:c14n1 .
+/// _:c14n1 <http://example.org/vocab#next> _:c14n0 .
+/// "#,
+/// );
+/// let not_canonical = parse(
+///     r#"<urn:ex:s> <urn:ex:p> _:e1 .
+/// _:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// "#,
+/// );
+///
+/// assert!(is_normalized_dataset(&normalized).unwrap());
+/// assert!(!is_normalized_dataset(&swapped).unwrap());
+/// assert!(!is_normalized_dataset(&not_canonical).unwrap());
+/// ```
+pub fn is_normalized_dataset(input_dataset: &Dataset) -> Result<bool, CanonicalizationError> {
+    // the labels must be exactly c14n0, ..., c14n{n-1}
+    let identifiers = blank_node_identifiers(input_dataset);
+    let mut counters: Vec<usize> = Vec::with_capacity(identifiers.len());
+    for identifier in identifiers.iter() {
+        let counter = identifier
+            .strip_prefix(CanonicalizationState::DEFAULT_CANONICAL_IDENTIFER_PREFIX)
+            .filter(|c| *c == "0" || (!c.is_empty() && !c.starts_with('0')))
+            .and_then(|c| c.parse().ok());
+        match counter {
+            Some(counter) => counters.push(counter),
+            None => return Ok(false),
+        }
+    }
+    counters.sort_unstable();
+    if counters.iter().enumerate().any(|(i, c)| i != *c) {
+        return Ok(false);
+    }
+
+    // every blank node must be labeled with its canonical identifier
+    let issued_identifiers_map = issue(input_dataset)?;
+    Ok(issued_identifiers_map.iter().all(|(k, v)| k == v))
+}
+
+/// An estimate of the cost of canonicalizing a dataset,
+/// based on the first degree hashes of its blank nodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ComplexityEstimate {
+    /// The number of blank nodes in the dataset
+    pub blank_node_count: usize,
+    /// The number of blank nodes sharing their first degree hash with others,
+    /// for which the Hash N-Degree Quads algorithm has to run
+    pub shared_blank_node_count: usize,
+    /// The number of groups of blank nodes sharing a first degree hash
+    pub shared_bucket_count: usize,
+    /// The size of the largest group of blank nodes sharing a first degree hash
+    pub largest_bucket_size: usize,
+}
+
+/// Estimates the cost of canonicalizing the input dataset by running only the cheap
+/// steps of the canonicalization algorithm, i.e., computing the first degree hashes
+/// and identifying the blank nodes that they distinguish uniquely.
+/// The remaining blank nodes require the Hash N-Degree Quads algorithm,
+/// whose cost grows very fast with the size of the groups sharing a hash,
+/// so large groups suggest an expensive or adversarial input.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{estimate_complexity, ComplexityEstimate};
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e2 .
+/// _:e2 <http://example.org/vocab#next> _:e0 .
+/// _:u <http://example.org/vocab#label> "unique" .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+///
+/// assert_eq!(
+///     estimate_complexity(&input_dataset).unwrap(),
+///     ComplexityEstimate {
+///         blank_node_count: 4,
+///         shared_blank_node_count: 3,
+///         shared_bucket_count: 1,
+///         largest_bucket_size: 3,
+///     }
+/// );
+/// ```
+pub fn estimate_complexity(
+    input_dataset: &Dataset,
+) -> Result<ComplexityEstimate, CanonicalizationError> {
+    let bucket_sizes = first_degree_hash_bucket_sizes(input_dataset)?;
+
+    let mut estimate = ComplexityEstimate {
+        blank_node_count: bucket_sizes.iter().sum(),
+        ..Default::default()
+    };
+    for &size in bucket_sizes.iter().filter(|&&size| size > 1) {
+        estimate.shared_blank_node_count += size;
+        estimate.shared_bucket_count += 1;
+        estimate.largest_bucket_size = estimate.largest_bucket_size.max(size);
+    }
+    Ok(estimate)
+}
+
+/// The distribution of the first degree hashes of the blank nodes in a dataset,
+/// as computed in step 3) of the canonicalization algorithm.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashDistributionStats {
+    /// The number of blank nodes in the dataset
+    pub blank_node_count: usize,
+    /// The number of distinct first degree hashes
+    pub distinct_hash_count: usize,
+    /// The number of blank nodes sharing the most common first degree hash
+    pub largest_bucket_size: usize,
+    /// The number of first degree hashes shared by exactly `k` blank nodes, keyed by `k`
+    pub bucket_size_histogram: BTreeMap<usize, usize>,
+}
+
+/// Returns statistics on the distribution of the first degree hashes of the blank nodes
+/// in the input dataset, running only step 3) of the canonicalization algorithm.
+/// Blank nodes with a unique hash are labeled cheaply, whereas those sharing a hash require
+/// the Hash N-Degree Quads algorithm, so a single hash covering most of the blank nodes
+/// is a red flag for an expensive or adversarial input.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::hash_distribution_stats;
+/// use std::collections::BTreeMap;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e2 .
+/// _:e2 <http://example.org/vocab#next> _:e0 .
+/// _:u <http://example.org/vocab#label> "unique" .
+/// _:v <http://example.org/vocab#label> "another" .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let stats = hash_distribution_stats(&input_dataset).unwrap();
+///
+/// assert_eq!(stats.blank_node_count, 5);
+/// assert_eq!(stats.distinct_hash_count, 3);
+/// assert_eq!(stats.largest_bucket_size, 3);
+/// assert_eq!(stats.bucket_size_histogram, BTreeMap::from([(1, 2), (3, 1)]));
+/// ```
+pub fn hash_distribution_stats(
+    input_dataset: &Dataset,
+) -> Result<HashDistributionStats, CanonicalizationError> {
+    let bucket_sizes = first_degree_hash_bucket_sizes(input_dataset)?;
+
+    let mut stats = HashDistributionStats {
+        blank_node_count: bucket_sizes.iter().sum(),
+        distinct_hash_count: bucket_sizes.len(),
+        ..Default::default()
+    };
+    for &size in &bucket_sizes {
+        *stats.bucket_size_histogram.entry(size).or_default() += 1;
+        stats.largest_bucket_size = stats.largest_bucket_size.max(size);
+    }
+    Ok(stats)
+}
+
+/// Returns the number of blank nodes sharing each first degree hash in the input dataset,
+/// as computed in step 3) of the canonicalization algorithm, in no particular order.
+fn first_degree_hash_bucket_sizes(
+    input_dataset: &Dataset,
+) -> Result<Vec<usize>, CanonicalizationError> {
+    let hashes = first_degree_hashes::<Sha256>(input_dataset)?;
+    let mut bucket_sizes = HashMap::<&str, usize>::new();
+    for hash in hashes.values() {
+        *bucket_sizes.entry(hash).or_default() += 1;
+    }
+    Ok(bucket_sizes.into_values().collect())
+}
+
+/// The step of the canonicalization algorithm in which a blank node got its canonical identifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LabelingStep {
+    /// Step 4), since no other blank node shares its first degree hash
+    UniqueFirstDegreeHash,
+    /// Step 5), using the Hash N-Degree Quads algorithm to distinguish it
+    /// from the other blank nodes sharing its first degree hash
+    NDegreeHash,
+}
+
+/// Why a blank node received its canonical identifier, as returned by `explain_labeling`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LabelExplanation {
+    /// The canonical identifier issued for the blank node
+    pub canonical_identifier: String,
+    /// The result of the Hash First Degree Quads algorithm for the blank node
+    pub first_degree_hash: String,
+    /// The step in which the canonical identifier was issued
+    pub labeling_step: LabelingStep,
+    /// The identifiers of the blank nodes sharing the first degree hash,
+    /// including the blank node itself, in code point order
+    pub bucket: Vec<String>,
+}
+
+/// Explains why the blank node with the given identifier in the input dataset
+/// received its canonical identifier: whether its first degree hash is unique,
+/// so that it was labeled in the cheap step 4) of the canonicalization algorithm,
+/// or it shares the hash with other blank nodes and was labeled in the expensive step 5).
+/// Returns `CanonicalizationError::CanonicalIdentifierNotExist` if the input dataset
+/// has no blank node with the identifier.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{explain_labeling, LabelingStep};
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// _:u <http://example.org/vocab#label> "unique" .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+///
+/// let unique = explain_labeling(&input_dataset, "u").unwrap();
+/// assert_eq!(unique.canonical_identifier, "c14n0");
+/// assert_eq!(unique.labeling_step, LabelingStep::UniqueFirstDegreeHash);
+/// assert_eq!(unique.bucket, vec!["u"]);
+///
+/// let shared = explain_labeling(&input_dataset, "e1").unwrap();
+/// assert_eq!(shared.labeling_step, LabelingStep::NDegreeHash);
+/// assert_eq!(shared.bucket, vec!["e0", "e1"]);
+/// assert_eq!(
+///     shared.first_degree_hash,
+///     explain_labeling(&input_dataset, "e0").unwrap().first_degree_hash
+/// );
+///
+/// assert!(explain_labeling(&input_dataset, "x").is_err());
+/// ```
+pub fn explain_labeling(
+    input_dataset: &Dataset,
+    original_id: &str,
+) -> Result<LabelExplanation, CanonicalizationError> {
+    let hashes = first_degree_hashes::<Sha256>(input_dataset)?;
+    let first_degree_hash = hashes
+        .get(original_id)
+        .ok_or(CanonicalizationError::CanonicalIdentifierNotExist)?
+        .clone();
+    let canonical_identifier = issue(input_dataset)?
+        .remove(original_id)
+        .ok_or(CanonicalizationError::CanonicalIdentifierNotExist)?;
+
+    // the keys of the map are in code point order
+    let bucket: Vec<String> = hashes
+        .into_iter()
+        .filter(|(_, hash)| *hash == first_degree_hash)
+        .map(|(id, _)| id)
+        .collect();
+    let labeling_step = if bucket.len() == 1 {
+        LabelingStep::UniqueFirstDegreeHash
+    } else {
+        LabelingStep::NDegreeHash
+    };
+    Ok(LabelExplanation {
+        canonical_identifier,
+        first_degree_hash,
+        labeling_step,
+        bucket,
+    })
 }
@@ -0,0 +1,200 @@
+use crate::{canon::first_degree_hashes, CanonicalizationError};
+use digest::Digest;
+use oxrdf::{Dataset, GraphNameRef, SubjectRef, TermRef};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A component of a quad, where blank nodes are replaced by their index
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Component {
+    BlankNode(usize),
+    Other(String),
+}
+
+/// The blank node structure of a dataset, on which automorphisms are searched
+struct BlankNodeStructure {
+    /// first degree hash of each blank node, which any automorphism preserves
+    colors: Vec<String>,
+    /// quads whose blank nodes are replaced by their index
+    quads: Vec<[Component; 4]>,
+    /// the same quads, for membership tests
+    quad_set: HashSet<[Component; 4]>,
+    /// indices of the quads in which each blank node appears
+    incident_quads: Vec<Vec<usize>>,
+}
+
+impl BlankNodeStructure {
+    fn new(input_dataset: &Dataset, hashes: Vec<(String, String)>) -> Self {
+        let index: HashMap<&str, usize> = hashes
+            .iter()
+            .enumerate()
+            .map(|(i, (id, _))| (id.as_str(), i))
+            .collect();
+        let mut incident_quads = vec![Vec::new(); hashes.len()];
+        let mut quads = Vec::new();
+        for quad in input_dataset.iter() {
+            let quad_index = quads.len();
+            let mut blank_node = |id: &str| {
+                let i = index[id];
+                if incident_quads[i].last() != Some(&quad_index) {
+                    incident_quads[i].push(quad_index);
+                }
+                Component::BlankNode(i)
+            };
+            let subject = match quad.subject {
+                SubjectRef::BlankNode(n) => blank_node(n.as_str()),
+                s => Component::Other(s.to_string()),
+            };
+            let object = match quad.object {
+                TermRef::BlankNode(n) => blank_node(n.as_str()),
+                o => Component::Other(o.to_string()),
+            };
+            let graph_name = match quad.graph_name {
+                GraphNameRef::BlankNode(n) => blank_node(n.as_str()),
+                g => Component::Other(g.to_string()),
+            };
+            let predicate = Component::Other(quad.predicate.to_string());
+            quads.push([subject, predicate, object, graph_name]);
+        }
+        BlankNodeStructure {
+            colors: hashes.into_iter().map(|(_, hash)| hash).collect(),
+            quad_set: quads.iter().cloned().collect(),
+            quads,
+            incident_quads,
+        }
+    }
+
+    /// Orders blank nodes breadth-first along shared quads, so that each
+    /// assignment is constrained by the previously assigned ones
+    fn search_order(&self) -> Vec<usize> {
+        let mut visited = vec![false; self.colors.len()];
+        let mut order = Vec::with_capacity(self.colors.len());
+        for root in 0..self.colors.len() {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut queue = VecDeque::from([root]);
+            while let Some(v) = queue.pop_front() {
+                order.push(v);
+                for &q in &self.incident_quads[v] {
+                    for component in &self.quads[q] {
+                        if let Component::BlankNode(u) = component {
+                            if !visited[*u] {
+                                visited[*u] = true;
+                                queue.push_back(*u);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        order
+    }
+
+    /// Checks that every quad around `v` whose blank nodes are all mapped
+    /// is mapped onto a quad of the dataset
+    fn is_consistent(&self, mapping: &[Option<usize>], v: usize) -> bool {
+        self.incident_quads[v].iter().all(|&q| {
+            let mut mapped = self.quads[q].clone();
+            for component in mapped.iter_mut() {
+                if let Component::BlankNode(u) = component {
+                    match mapping[*u] {
+                        Some(w) => *u = w,
+                        None => return true,
+                    }
+                }
+            }
+            self.quad_set.contains(&mapped)
+        })
+    }
+
+    /// Tries to extend the partial mapping to an automorphism by backtracking
+    fn extend(&self, order: &[usize], mapping: &mut [Option<usize>], used: &mut [bool]) -> bool {
+        let Some(position) = order.iter().position(|&v| mapping[v].is_none()) else {
+            return true;
+        };
+        let v = order[position];
+        for w in 0..self.colors.len() {
+            if used[w] || self.colors[w] != self.colors[v] {
+                continue;
+            }
+            mapping[v] = Some(w);
+            used[w] = true;
+            if self.is_consistent(mapping, v) && self.extend(order, mapping, used) {
+                return true;
+            }
+            mapping[v] = None;
+            used[w] = false;
+        }
+        false
+    }
+
+    /// Checks whether some automorphism fixes all the nodes in `fixed` and maps `v` to `w`
+    fn exists_automorphism(&self, order: &[usize], fixed: &[usize], v: usize, w: usize) -> bool {
+        let mut mapping = vec![None; self.colors.len()];
+        let mut used = vec![false; self.colors.len()];
+        for &u in fixed {
+            mapping[u] = Some(u);
+            used[u] = true;
+        }
+        if used[w] {
+            return false;
+        }
+        mapping[v] = Some(w);
+        used[w] = true;
+        self.is_consistent(&mapping, v) && self.extend(order, &mut mapping, &mut used)
+    }
+}
+
+/// Returns the number of automorphisms of the input dataset, i.e., the number
+/// of permutations of its blank nodes that map the dataset onto itself.
+/// This equals the number of distinct blank node labelings that yield the same
+/// canonical form.
+///
+/// The count is computed exactly as the product of orbit sizes along a chain of
+/// stabilizers, where candidate images are restricted to blank nodes sharing the
+/// same first degree hash (computed with `D`).
+/// Note that merely counting permutations tying for the chosen path in the Hash
+/// N-Degree Quads algorithm does not give this number in general.
+/// The search is exponential in the worst case, so it is intended for
+/// analysis of moderately sized datasets. The result saturates at `u64::MAX`.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::automorphism_count;
+/// use sha2::Sha256;
+/// use std::io::Cursor;
+///
+/// // a directed cycle of three blank nodes can be rotated in three ways
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e2 .
+/// _:e2 <http://example.org/vocab#next> _:e0 .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+///
+/// assert_eq!(automorphism_count::<Sha256>(&input_dataset).unwrap(), 3);
+/// ```
+pub fn automorphism_count<D: Digest>(
+    input_dataset: &Dataset,
+) -> Result<u64, CanonicalizationError> {
+    let hashes = first_degree_hashes::<D>(input_dataset)?;
+    let structure = BlankNodeStructure::new(input_dataset, hashes.into_iter().collect());
+    let order = structure.search_order();
+
+    let mut count: u64 = 1;
+    for (i, &v) in order.iter().enumerate() {
+        let fixed = &order[..i];
+        let orbit_size = (0..structure.colors.len())
+            .filter(|&w| structure.exists_automorphism(&order, fixed, v, w))
+            .count();
+        count = count.saturating_mul(orbit_size as u64);
+    }
+    Ok(count)
+}
@@ -0,0 +1,260 @@
+use oxrdf::Dataset;
+use oxttl::NQuadsParser;
+use rdf_canon::{
+    canonicalize_with_algorithm, issue_with_algorithm, CanonicalizationError,
+    CanonicalizationOptions, HashAlgorithm,
+};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufReader, Read, Write},
+    process::ExitCode,
+};
+
+const USAGE: &str = "\
+Usage: rdf-canon [OPTIONS] [FILE]
+
+Reads an N-Quads document from FILE, or from the standard input if FILE is
+not given or is -, and writes its canonical form (RDFC-1.0) to the standard
+output.
+
+Options:
+  --hash <ALGORITHM>  Hash algorithm: sha256 (default), sha384, or sha512
+  --call-limit <N>    Limit on the calls to the Hash N-Degree Quads algorithm
+  --map               Write the issued identifiers map as JSON instead
+  -h, --help          Print this help
+
+Exit status: 0 on success, 1 on an invalid input or any other error,
+2 on invalid arguments, 3 if the call limit was exceeded.";
+
+struct Args {
+    hash_algorithm: HashAlgorithm,
+    call_limit: Option<usize>,
+    map: bool,
+    input: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut parsed = Args {
+        hash_algorithm: HashAlgorithm::default(),
+        call_limit: None,
+        map: false,
+        input: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--hash" => {
+                let value = args.next().ok_or("--hash requires a value")?;
+                parsed.hash_algorithm = value.parse().map_err(|e| format!("{}", e))?;
+            }
+            "--call-limit" => {
+                let value = args.next().ok_or("--call-limit requires a value")?;
+                parsed.call_limit = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid call limit: {}", value))?,
+                );
+            }
+            "--map" => parsed.map = true,
+            "-" => parsed.input = None,
+            _ if arg.starts_with('-') => return Err(format!("unknown option: {}", arg)),
+            _ if parsed.input.is_some() => return Err(format!("unexpected argument: {}", arg)),
+            _ => parsed.input = Some(arg),
+        }
+    }
+    Ok(Some(parsed))
+}
+
+/// Serializes the map as a JSON object with sorted keys,
+/// in the format of the map files of the test suite.
+fn to_json(issued_identifiers_map: &BTreeMap<&String, &String>) -> String {
+    fn quote(s: &str) -> String {
+        let mut quoted = String::from("\"");
+        for c in s.chars() {
+            match c {
+                '"' => quoted.push_str("\\\""),
+                '\\' => quoted.push_str("\\\\"),
+                c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+
+    if issued_identifiers_map.is_empty() {
+        return "{}\n".to_string();
+    }
+    let entries: Vec<String> = issued_identifiers_map
+        .iter()
+        .map(|(k, v)| format!("  {}: {}", quote(k), quote(v)))
+        .collect();
+    format!("{{\n{}\n}}\n", entries.join(",\n"))
+}
+
+fn run(args: &Args) -> Result<String, CanonicalizationError> {
+    let reader: Box<dyn Read> = match &args.input {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(io::stdin().lock()),
+    };
+    let parser = NQuadsParser::new();
+    #[cfg(feature = "rdf-star")]
+    let parser = parser.with_quoted_triples();
+    let input_dataset = parser.for_reader(reader).collect::<Result<Dataset, _>>()?;
+    let options = CanonicalizationOptions {
+        hndq_call_limit: args.call_limit,
+        ..Default::default()
+    };
+
+    if args.map {
+        let issued_identifiers_map =
+            issue_with_algorithm(&input_dataset, args.hash_algorithm, &options)?;
+        Ok(to_json(&issued_identifiers_map.iter().collect()))
+    } else {
+        canonicalize_with_algorithm(&input_dataset, args.hash_algorithm, &options)
+    }
+}
+
+/// Returns the exit status for the error, as documented in [`USAGE`].
+fn error_exit_code(e: &CanonicalizationError) -> u8 {
+    match e {
+        CanonicalizationError::HndqCallLimitExceeded { .. } => 3,
+        _ => 1,
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            // (ignoring a closed pipe, e.g., when piped into head)
+            let _ = writeln!(io::stdout(), "{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("rdf-canon: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&args) {
+        Ok(output) => match io::stdout().lock().write_all(output.as_bytes()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("rdf-canon: {}", e);
+                ExitCode::FAILURE
+            }
+        },
+        Err(e) => {
+            eprintln!("rdf-canon: {}", e);
+            ExitCode::from(error_exit_code(&e))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parse_options_and_input() {
+        let args = parse(&[
+            "--hash",
+            "sha384",
+            "--call-limit",
+            "10",
+            "--map",
+            "input.nq",
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(args.hash_algorithm, HashAlgorithm::Sha384);
+        assert_eq!(args.call_limit, Some(10));
+        assert!(args.map);
+        assert_eq!(args.input.as_deref(), Some("input.nq"));
+
+        let args = parse(&[]).unwrap().unwrap();
+        assert_eq!(args.hash_algorithm, HashAlgorithm::Sha256);
+        assert_eq!(args.call_limit, None);
+        assert!(!args.map);
+        assert_eq!(args.input, None);
+
+        assert!(parse(&["--map", "-h"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn parse_standard_input() {
+        let args = parse(&["-"]).unwrap().unwrap();
+        assert_eq!(args.input, None);
+        // (- can also be given after a file, to read the standard input instead)
+        let args = parse(&["input.nq", "-"]).unwrap().unwrap();
+        assert_eq!(args.input, None);
+    }
+
+    #[test]
+    fn parse_invalid_args() {
+        assert_eq!(
+            parse(&["--verbose"]).err().unwrap(),
+            "unknown option: --verbose"
+        );
+        assert_eq!(parse(&["--hash"]).err().unwrap(), "--hash requires a value");
+        assert_eq!(
+            parse(&["--call-limit"]).err().unwrap(),
+            "--call-limit requires a value"
+        );
+        assert_eq!(
+            parse(&["--call-limit", "many"]).err().unwrap(),
+            "invalid call limit: many"
+        );
+        assert!(parse(&["--hash", "md5"]).is_err());
+        assert_eq!(
+            parse(&["a.nq", "b.nq"]).err().unwrap(),
+            "unexpected argument: b.nq"
+        );
+    }
+
+    #[test]
+    fn map_to_json() {
+        assert_eq!(to_json(&BTreeMap::new()), "{}\n");
+
+        let (e0, e1, c0, c1) = (
+            "e0".to_string(),
+            "e\"1\\\n\u{1}".to_string(),
+            "c14n0".to_string(),
+            "c14n1".to_string(),
+        );
+        // (sorted by key, and " sorts before 0)
+        let map = BTreeMap::from([(&e0, &c0), (&e1, &c1)]);
+        assert_eq!(
+            to_json(&map),
+            "{\n  \"e\\\"1\\\\\\u000a\\u0001\": \"c14n1\",\n  \"e0\": \"c14n0\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn exit_code_on_call_limit_exceeded() {
+        let args = Args {
+            hash_algorithm: HashAlgorithm::Sha256,
+            call_limit: Some(1),
+            map: false,
+            input: Some("tests/rdfc10/test044-in.nq".to_string()),
+        };
+        let e = run(&args).unwrap_err();
+        assert!(matches!(
+            e,
+            CanonicalizationError::HndqCallLimitExceeded { .. }
+        ));
+        assert_eq!(error_exit_code(&e), 3);
+
+        let args = Args {
+            input: Some("tests/rdfc10/missing-in.nq".to_string()),
+            ..args
+        };
+        assert_eq!(error_exit_code(&run(&args).unwrap_err()), 1);
+    }
+}
@@ -1,43 +1,84 @@
 use crate::{
-    counter::{HndqCallCounter, SimpleHndqCallCounter},
+    counter::{HndqCallCounter, MaybeSend},
     error::CanonicalizationError,
 };
 use digest::Digest;
-use itertools::Itertools;
+use indexmap::IndexMap;
 use oxrdf::{
-    BlankNode, Dataset, Graph, GraphName, GraphNameRef, Quad, QuadRef, Subject, SubjectRef, Term,
-    TermRef, TripleRef,
+    BlankNode, Dataset, Graph, GraphName, GraphNameRef, Quad, Subject, SubjectRef, Term, TermRef,
+    TripleRef,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Write},
 };
-use std::collections::{BTreeMap, HashMap};
 
+#[cfg(feature = "transcript")]
+use crate::transcript::{TranscriptEntry, TranscriptOperation};
+#[cfg(any(feature = "transcript", feature = "parallel"))]
+use std::sync::Mutex;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+#[cfg(feature = "rdf-star")]
+use oxrdf::{QuadRef, Triple};
+
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, Zeroizing};
+
+#[cfg(feature = "log")]
+use itertools::Itertools;
 #[cfg(feature = "log")]
 use tracing::{debug, debug_span, info};
 
 /// **4.2 Canonicalization State**
-struct CanonicalizationState {
+pub(crate) struct CanonicalizationState {
     /// **blank node to quads map**
     ///   A map that relates a blank node identifier to the quads
     ///   in which they appear in the input dataset.
-    blank_node_to_quads_map: BTreeMap<String, Vec<Quad>>,
+    ///   (The quads are referred to by their indices in `quads`.)
+    blank_node_to_quads_map: BTreeMap<String, Vec<usize>>,
+
+    /// The quads of the input dataset that contain blank nodes, each stored once
+    /// however many blank nodes it contains
+    quads: Vec<Quad>,
 
     /// **hash to blank nodes map**
     ///   A map that relates a hash to a list of blank node identifiers.
     hash_to_blank_node_map: BTreeMap<String, Vec<String>>,
 
+    /// The first degree hash of every blank node computed in step 3 of the canonicalization
+    /// algorithm, kept so that the Hash Related Blank Node algorithm can look it up
+    /// instead of running the Hash First Degree Quads algorithm again
+    first_degree_hash_map: HashMap<String, String>,
+
     /// **canonical issuer**
     ///   An identifier issuer, initialized with the prefix c14n, for
     ///   issuing canonical blank node identifiers.
     canonical_issuer: IdentifierIssuer,
+
+    /// Hashing inputs and outputs recorded in order, if requested
+    #[cfg(feature = "transcript")]
+    transcript: Option<Mutex<Vec<TranscriptEntry>>>,
+
+    /// The paths chosen by the Hash N-Degree Quads algorithm in step 5, if requested
+    chosen_path_traces: Option<Vec<ChosenPathTrace>>,
 }
 
 impl CanonicalizationState {
-    const DEFAULT_CANONICAL_IDENTIFER_PREFIX: &'static str = "c14n";
+    pub(crate) const DEFAULT_CANONICAL_IDENTIFER_PREFIX: &'static str = "c14n";
 
     fn new() -> CanonicalizationState {
         CanonicalizationState {
-            blank_node_to_quads_map: BTreeMap::<String, Vec<Quad>>::new(),
+            blank_node_to_quads_map: BTreeMap::<String, Vec<usize>>::new(),
+            quads: Vec::new(),
             hash_to_blank_node_map: BTreeMap::<String, Vec<String>>::new(),
+            first_degree_hash_map: HashMap::<String, String>::new(),
             canonical_issuer: IdentifierIssuer::new(Self::DEFAULT_CANONICAL_IDENTIFER_PREFIX),
+            #[cfg(feature = "transcript")]
+            transcript: None,
+            chosen_path_traces: None,
         }
     }
 
@@ -45,14 +86,17 @@ impl CanonicalizationState {
         // **4.4.3 Algorithm**
         // 2) For every quad Q in input dataset:
         for quad in dataset.iter() {
+            let index = self.quads.len();
+            let mut referenced = false;
             // 2.1) For each blank node that is a component of Q, add a reference to Q from the map
             // entry for the blank node identifier identifier in the blank node to quads map,
             // creating a new entry if necessary.
             if let SubjectRef::BlankNode(n) = &quad.subject {
                 self.blank_node_to_quads_map
                     .entry(n.as_str().to_string())
-                    .or_insert_with(Vec::<Quad>::new)
-                    .push(quad.into());
+                    .or_default()
+                    .push(index);
+                referenced = true;
             }
             // 2.1) For each blank node that is a component of Q, add a reference to Q from the map
             // entry for the blank node identifier identifier in the blank node to quads map,
@@ -60,8 +104,9 @@ impl CanonicalizationState {
             if let TermRef::BlankNode(n) = &quad.object {
                 self.blank_node_to_quads_map
                     .entry(n.as_str().to_string())
-                    .or_insert_with(Vec::<Quad>::new)
-                    .push(quad.into());
+                    .or_default()
+                    .push(index);
+                referenced = true;
             }
             // 2.1) For each blank node that is a component of Q, add a reference to Q from the map
             // entry for the blank node identifier identifier in the blank node to quads map,
@@ -69,31 +114,103 @@ impl CanonicalizationState {
             if let GraphNameRef::BlankNode(n) = &quad.graph_name {
                 self.blank_node_to_quads_map
                     .entry(n.as_str().to_string())
-                    .or_insert_with(Vec::<Quad>::new)
-                    .push(quad.into());
+                    .or_default()
+                    .push(index);
+                referenced = true;
+            }
+            // (blank nodes in quoted triples are components of Q as well)
+            #[cfg(feature = "rdf-star")]
+            for n in quoted_blank_node_identifiers_in_quad(quad) {
+                self.blank_node_to_quads_map
+                    .entry(n.to_string())
+                    .or_default()
+                    .push(index);
+                referenced = true;
+            }
+            if referenced {
+                self.quads.push(quad.into());
             }
         }
     }
 
-    fn get_quads_for_blank_node(&self, identifier: &String) -> Option<&Vec<Quad>> {
-        self.blank_node_to_quads_map.get(identifier)
+    fn get_quads_for_blank_node(
+        &self,
+        identifier: &String,
+    ) -> Option<impl Iterator<Item = &Quad> + Clone + '_> {
+        self.blank_node_to_quads_map
+            .get(identifier)
+            .map(|indices| indices.iter().map(|&i| &self.quads[i]))
+    }
+
+    #[cfg(feature = "transcript")]
+    /// Records the concatenation of the input pieces, which is only built if recording.
+    fn record<S: AsRef<str>>(&self, operation: TranscriptOperation, input: &[S], output: &str) {
+        if let Some(transcript) = &self.transcript {
+            transcript.lock().unwrap().push(TranscriptEntry {
+                operation,
+                input: input.iter().flat_map(|s| s.as_ref().bytes()).collect(),
+                output: output.to_string(),
+            });
+        }
     }
 
     #[cfg(feature = "log")]
     fn serialize_blank_node_to_quads_map(&self) -> BTreeMap<String, Vec<String>> {
         self.blank_node_to_quads_map
             .iter()
-            .map(|(k, v)| (k.clone(), v.iter().map(|q| q.to_string() + " .").collect()))
+            .map(|(k, v)| {
+                (
+                    k.clone(),
+                    v.iter()
+                        .map(|&i| self.quads[i].to_string() + " .")
+                        .collect(),
+                )
+            })
             .collect()
     }
 }
 
+/// Returns the identifiers of the blank nodes in the quoted triple, including those in
+/// quoted triples nested in it, in the order of their occurrence.
+#[cfg(feature = "rdf-star")]
+pub(crate) fn quoted_blank_node_identifiers(triple: &Triple) -> Vec<&str> {
+    let mut identifiers = Vec::new();
+    match &triple.subject {
+        Subject::BlankNode(n) => identifiers.push(n.as_str()),
+        Subject::Triple(t) => identifiers.extend(quoted_blank_node_identifiers(t)),
+        _ => {}
+    }
+    match &triple.object {
+        Term::BlankNode(n) => identifiers.push(n.as_str()),
+        Term::Triple(t) => identifiers.extend(quoted_blank_node_identifiers(t)),
+        _ => {}
+    }
+    identifiers
+}
+
+/// Returns the identifiers of the blank nodes in the quoted triples
+/// in the subject and object of the quad, in this order.
+#[cfg(feature = "rdf-star")]
+pub(crate) fn quoted_blank_node_identifiers_in_quad(quad: QuadRef<'_>) -> Vec<&str> {
+    let mut identifiers = Vec::new();
+    if let SubjectRef::Triple(t) = quad.subject {
+        identifiers.extend(quoted_blank_node_identifiers(t));
+    }
+    if let TermRef::Triple(t) = quad.object {
+        identifiers.extend(quoted_blank_node_identifiers(t));
+    }
+    identifiers
+}
+
 /// **4.3 Blank Node Identifier Issuer State**
 /// During the canonicalization algorithm, it is sometimes necessary to issue new identifiers to blank nodes.
 /// The Issue Identifier algorithm uses an identifier issuer to accomplish this task.
 /// The information an identifier issuer needs to keep track of is described below.
+///
+/// The issued identifiers map is kept in issuance order, which can be inspected via
+/// [`IdentifierIssuer::issued_identifiers_map`], e.g., to reproduce the c14n0, c14n1, ... sequence.
 #[derive(PartialEq, Eq, Clone, Debug)]
-struct IdentifierIssuer {
+pub struct IdentifierIssuer {
     /// **identifier prefix**
     ///   The identifier prefix is a string that is used at the
     ///   beginning of an blank node identifier. It should be initialized
@@ -114,12 +231,14 @@ struct IdentifierIssuer {
     ///   identifiers, to prevent issuance of more than one new identifier
     ///   per existing identifier, and to allow blank nodes to be
     ///   reassigned identifiers some time after issuance.
-    issued_identifiers_map: HashMap<String, String>,
+    issued_identifiers_map: IndexMap<String, String>,
 }
 
 impl IdentifierIssuer {
-    fn new(identifier_prefix: &str) -> IdentifierIssuer {
-        let issued_identifiers_map = HashMap::<String, String>::new();
+    /// Creates an identifier issuer issuing identifiers like `{identifier_prefix}0`,
+    /// `{identifier_prefix}1`, ...
+    pub fn new(identifier_prefix: &str) -> IdentifierIssuer {
+        let issued_identifiers_map = IndexMap::<String, String>::new();
         IdentifierIssuer {
             identifier_prefix: identifier_prefix.to_string(),
             identifier_counter: 0,
@@ -131,7 +250,21 @@ impl IdentifierIssuer {
         self.identifier_counter += 1
     }
 
-    fn get(&self, existing_identifier: &str) -> Option<String> {
+    /// Creates an identifier issuer that has already issued the given identifiers,
+    /// continuing the identifier counter after them.
+    pub(crate) fn with_issued_identifiers(
+        identifier_prefix: &str,
+        issued_identifiers_map: IndexMap<String, String>,
+    ) -> IdentifierIssuer {
+        IdentifierIssuer {
+            identifier_prefix: identifier_prefix.to_string(),
+            identifier_counter: issued_identifiers_map.len(),
+            issued_identifiers_map,
+        }
+    }
+
+    /// Returns the identifier issued for the existing identifier, if any.
+    pub fn get(&self, existing_identifier: &str) -> Option<String> {
         self.issued_identifiers_map
             .get(existing_identifier)
             .cloned()
@@ -146,7 +279,7 @@ impl IdentifierIssuer {
     /// **4.5.2 Algorithm**
     ///   The algorithm takes an identifier issuer I and an existing identifier as
     ///   inputs. The output is a new issued identifier.
-    fn issue(&mut self, existing_identifier: &str) -> String {
+    pub fn issue(&mut self, existing_identifier: &str) -> String {
         // 1) If there is a map entry for existing identifier in issued identifiers
         // map of I, return it.
         if let Some(issued_identifier) = self.get(existing_identifier) {
@@ -169,6 +302,47 @@ impl IdentifierIssuer {
         issued_identifier
     }
 
+    /// Returns a checkpoint of the issuer, to which it can be rolled back later.
+    fn checkpoint(&self) -> IssuerCheckpoint {
+        IssuerCheckpoint {
+            issued_identifiers_count: self.issued_identifiers_map.len(),
+            identifier_counter: self.identifier_counter,
+        }
+    }
+
+    /// Forgets the identifiers issued since the checkpoint, which is cheap as issuing
+    /// only ever appends to the issued identifiers map.
+    fn rollback(&mut self, checkpoint: IssuerCheckpoint) {
+        self.issued_identifiers_map
+            .truncate(checkpoint.issued_identifiers_count);
+        self.identifier_counter = checkpoint.identifier_counter;
+    }
+
+    /// Returns the entries of the issued identifiers map added since the checkpoint.
+    fn issued_since(&self, checkpoint: IssuerCheckpoint) -> Vec<(String, String)> {
+        self.issued_identifiers_map[checkpoint.issued_identifiers_count..]
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    /// Re-issues the entries returned by [`IdentifierIssuer::issued_since`]
+    /// after rolling back to the same checkpoint.
+    fn reissue(&mut self, issued: Vec<(String, String)>) {
+        self.identifier_counter += issued.len();
+        self.issued_identifiers_map.extend(issued);
+    }
+
+    /// Returns the map from existing identifiers to issued identifiers in issuance order.
+    pub fn issued_identifiers_map(&self) -> &IndexMap<String, String> {
+        &self.issued_identifiers_map
+    }
+
+    /// Consumes the issuer and returns its issued identifiers map in issuance order.
+    pub fn into_issued_identifiers_map(self) -> IndexMap<String, String> {
+        self.issued_identifiers_map
+    }
+
     #[cfg(feature = "log")]
     fn serialize_issued_identifiers_map(&self) -> String {
         format!(
@@ -181,21 +355,269 @@ impl IdentifierIssuer {
     }
 }
 
+/// The permutations of `0..n` in lexicographic order, the same order as
+/// `Itertools::permutations` yields them in, with the ability to skip
+/// the remaining permutations sharing a prefix with the current one.
+struct LexicographicPermutations {
+    indices: Vec<usize>,
+    started: bool,
+}
+
+impl LexicographicPermutations {
+    fn new(n: usize) -> Self {
+        LexicographicPermutations {
+            indices: (0..n).collect(),
+            started: false,
+        }
+    }
+
+    /// Advances to the next permutation and returns it, or `None` if there is none left.
+    fn next(&mut self) -> Option<&[usize]> {
+        if !self.started {
+            self.started = true;
+            return Some(&self.indices);
+        }
+        // find the rightmost ascent, swap it with the smallest greater element to its right,
+        // and reverse the (descending) suffix after it
+        let i = self.indices.windows(2).rposition(|w| w[0] < w[1])?;
+        let j = self.indices.iter().rposition(|&x| x > self.indices[i])?;
+        self.indices.swap(i, j);
+        self.indices[i + 1..].reverse();
+        Some(&self.indices)
+    }
+
+    /// Skips the remaining permutations starting with the first `len` elements of the
+    /// current one, by moving to the last of them.
+    fn skip_prefix(&mut self, len: usize) {
+        self.indices[len..].sort_unstable_by(|a, b| b.cmp(a));
+    }
+}
+
+/// The state of an [`IdentifierIssuer`] at some point, see [`IdentifierIssuer::checkpoint`].
+#[derive(Clone, Copy)]
+struct IssuerCheckpoint {
+    issued_identifiers_count: usize,
+    identifier_counter: usize,
+}
+
 /// **hash**
 ///   The lowercase, hexadecimal representation of a message digest.
 /// **hash algorithm**
 ///   The hash algorithm used by URDNA2015, namely, SHA-256.
 fn hash<D: Digest>(data: impl AsRef<[u8]>) -> String {
-    let hash = D::digest(data);
+    let hash = wiped(D::digest(data));
     base16ct::lower::encode_string(&hash)
 }
 
+/// Returns the same hash as [`hash`] does for the concatenation of the pieces,
+/// feeding them into the hash algorithm one by one instead of concatenating them.
+fn hash_concatenated<D: Digest>(pieces: &[impl AsRef<[u8]>]) -> String {
+    let mut hasher = D::new();
+    for piece in pieces {
+        hasher.update(piece);
+    }
+    let hash = wiped(hasher.finalize());
+    base16ct::lower::encode_string(&hash)
+}
+
+/// Wraps data passed to or returned by the hash algorithm so that it is wiped when dropped
+/// if the `zeroize` feature is enabled, including when returning early with an error
+/// or unwinding from a panic
+#[cfg(feature = "zeroize")]
+fn wiped<Z: Zeroize>(data: Z) -> Zeroizing<Z> {
+    Zeroizing::new(data)
+}
+
+/// Wraps data passed to or returned by the hash algorithm so that it is wiped when dropped
+/// if the `zeroize` feature is enabled, which it is not
+#[cfg(not(feature = "zeroize"))]
+fn wiped<Z>(data: Z) -> Z {
+    data
+}
+
+/// The serialized quads in the Hash First Degree Quads algorithm, allocated from an arena
+#[cfg(feature = "bump-alloc")]
+struct ArenaNQuads<'a>(Vec<bumpalo::collections::String<'a>>);
+
+#[cfg(feature = "bump-alloc")]
+impl<'a> std::ops::Deref for ArenaNQuads<'a> {
+    type Target = Vec<bumpalo::collections::String<'a>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "bump-alloc")]
+impl std::ops::DerefMut for ArenaNQuads<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(all(feature = "bump-alloc", feature = "zeroize"))]
+impl Zeroize for ArenaNQuads<'_> {
+    fn zeroize(&mut self) {
+        for nquad in self.0.iter_mut() {
+            nquad.as_mut_str().zeroize();
+        }
+    }
+}
+
 /// **4.4 Canonicalization Algorithm**
 /// The canonicalization algorithm converts an input dataset into a canonicalized dataset.
 /// This algorithm will assign deterministic identifiers to any blank nodes in the input dataset.
-pub fn canonicalize_core<D: Digest>(
+pub fn canonicalize_core<D: Digest, C: HndqCallCounter + MaybeSend>(
+    input_dataset: &Dataset,
+    mut hndq_call_counter: C,
+) -> Result<HashMap<String, String>, CanonicalizationError> {
+    // 1) Create the canonicalization state.
+    let mut state = CanonicalizationState::new();
+
+    canonicalize_with_state::<D, C>(&mut state, input_dataset, &mut hndq_call_counter)
+}
+
+/// Statistics on a run of the canonicalization algorithm,
+/// e.g., to see how close the input came to the call limit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CanonicalizationStats {
+    /// The number of calls to the Hash N-Degree Quads algorithm
+    pub hndq_calls: usize,
+    /// The number of blank nodes in the input dataset
+    pub blank_node_count: usize,
+    /// The maximum recursion depth reached by the Hash N-Degree Quads algorithm,
+    /// where the calls made directly by the canonicalization algorithm have depth 1,
+    /// or 0 if the algorithm is not called
+    pub max_depth: usize,
+}
+
+/// Runs the canonicalization algorithm as [`canonicalize_core`] does,
+/// and also returns statistics on the run.
+pub fn canonicalize_core_with_stats<D: Digest, C: HndqCallCounter + MaybeSend>(
+    input_dataset: &Dataset,
+    hndq_call_counter: C,
+) -> Result<(HashMap<String, String>, CanonicalizationStats), CanonicalizationError> {
+    canonicalize_core_with_progress::<D, C>(input_dataset, hndq_call_counter, &|_, _| {})
+}
+
+/// Runs the canonicalization algorithm as [`canonicalize_core_with_stats`] does,
+/// while reporting the progress of step 5), where the Hash N-Degree Quads algorithm
+/// runs for the blank nodes sharing a first degree hash.
+/// After each entry of the hash to blank nodes map is processed in step 5),
+/// `progress` is called with the number of entries processed so far and the total number of
+/// entries, in the order of processing; it is not called if no blank nodes share a hash.
+/// Most of the work is usually spent on a few of these entries, so this is a coarse but
+/// meaningful measure of the progress of the expensive part of the algorithm.
+pub fn canonicalize_core_with_progress<D: Digest, C: HndqCallCounter + MaybeSend>(
+    input_dataset: &Dataset,
+    mut hndq_call_counter: C,
+    progress: &dyn Fn(usize, usize),
+) -> Result<(HashMap<String, String>, CanonicalizationStats), CanonicalizationError> {
+    // 1) Create the canonicalization state.
+    let mut state = CanonicalizationState::new();
+
+    let issued_identifiers_map = canonicalize_with_state_and_progress::<D, C>(
+        &mut state,
+        input_dataset,
+        &mut hndq_call_counter,
+        progress,
+    )?;
+    let stats = CanonicalizationStats {
+        hndq_calls: hndq_call_counter.sum(),
+        blank_node_count: state.blank_node_to_quads_map.len(),
+        max_depth: hndq_call_counter.max_depth(),
+    };
+    Ok((issued_identifiers_map, stats))
+}
+
+/// The paths chosen by the Hash N-Degree Quads algorithm for a blank node
+/// in step 5.2) of the canonicalization algorithm,
+/// e.g., to compare the runs on two datasets that are expected to be isomorphic
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChosenPathTrace {
+    /// The blank node identifier the algorithm was run for
+    pub identifier: String,
+    /// Each related hash in code point order paired with the path chosen in step 5.4)
+    /// for the blank nodes with that hash, as appended to the data to hash
+    pub chosen_paths: Vec<(String, String)>,
+    /// The temporary identifiers issued by the resulting issuer, in issuance order
+    pub issued_identifiers: Vec<(String, String)>,
+    /// The resulting hash
+    pub hash: String,
+}
+
+/// What to record in a run of the canonicalization algorithm besides the issued identifiers
+#[derive(Default)]
+pub(crate) struct Recording {
+    /// Every input and output of the hash algorithm in the order in which they are computed,
+    /// if requested
+    #[cfg(feature = "transcript")]
+    pub(crate) transcript: Option<Vec<TranscriptEntry>>,
+    /// The paths chosen by the Hash N-Degree Quads algorithm in step 5), if requested
+    pub(crate) chosen_path_traces: Option<Vec<ChosenPathTrace>>,
+}
+
+/// Runs the canonicalization algorithm as [`canonicalize_core_with_progress`] does,
+/// but returns the canonical issuer, which keeps the issuance order, along with the statistics,
+/// and records what is requested in `recording`, even if the canonicalization fails.
+pub(crate) fn canonicalize_core_with_recording<D: Digest, C: HndqCallCounter + MaybeSend>(
+    input_dataset: &Dataset,
+    mut hndq_call_counter: C,
+    progress: &dyn Fn(usize, usize),
+    recording: &mut Recording,
+) -> Result<(IdentifierIssuer, CanonicalizationStats), CanonicalizationError> {
+    // 1) Create the canonicalization state.
+    let mut state = CanonicalizationState::new();
+    #[cfg(feature = "transcript")]
+    if recording.transcript.is_some() {
+        state.transcript = Some(Mutex::default());
+    }
+    state.chosen_path_traces = recording.chosen_path_traces.take();
+
+    let result = canonicalize_with_state_and_progress::<D, C>(
+        &mut state,
+        input_dataset,
+        &mut hndq_call_counter,
+        progress,
+    );
+
+    #[cfg(feature = "transcript")]
+    if let (Some(transcript), Some(recorded)) =
+        (recording.transcript.as_mut(), state.transcript.take())
+    {
+        transcript.extend(recorded.into_inner().unwrap());
+    }
+    recording.chosen_path_traces = state.chosen_path_traces.take();
+    result?;
+    let stats = CanonicalizationStats {
+        hndq_calls: hndq_call_counter.sum(),
+        blank_node_count: state.blank_node_to_quads_map.len(),
+        max_depth: hndq_call_counter.max_depth(),
+    };
+    Ok((state.canonical_issuer, stats))
+}
+
+/// Performs steps 2) to 6) of the canonicalization algorithm on the given state.
+fn canonicalize_with_state<D: Digest, C: HndqCallCounter + MaybeSend>(
+    state: &mut CanonicalizationState,
     input_dataset: &Dataset,
-    mut hndq_call_counter: SimpleHndqCallCounter,
+    hndq_call_counter: &mut C,
+) -> Result<HashMap<String, String>, CanonicalizationError> {
+    canonicalize_with_state_and_progress::<D, C>(
+        state,
+        input_dataset,
+        hndq_call_counter,
+        &|_, _| {},
+    )
+}
+
+/// Performs steps 2) to 6) of the canonicalization algorithm on the given state,
+/// reporting the progress of step 5) as described in [`canonicalize_core_with_progress`].
+fn canonicalize_with_state_and_progress<D: Digest, C: HndqCallCounter + MaybeSend>(
+    state: &mut CanonicalizationState,
+    input_dataset: &Dataset,
+    hndq_call_counter: &mut C,
+    progress: &dyn Fn(usize, usize),
 ) -> Result<HashMap<String, String>, CanonicalizationError> {
     #[cfg(feature = "log")]
     let _span_ca = debug_span!(
@@ -204,9 +626,6 @@ pub fn canonicalize_core<D: Digest>(
     )
     .entered();
 
-    // 1) Create the canonicalization state.
-    let mut state = CanonicalizationState::new();
-
     // 2) For every quad Q in input dataset:
     #[cfg(feature = "log")]
     let span_ca_2 = debug_span!(
@@ -233,6 +652,12 @@ pub fn canonicalize_core<D: Digest>(
     #[cfg(feature = "log")]
     span_ca_2.exit();
 
+    // without blank nodes there is nothing to hash or issue,
+    // so the canonicalized dataset only differs from the input dataset in its serialization
+    if state.blank_node_to_quads_map.is_empty() {
+        return Ok(HashMap::new());
+    }
+
     // 3) For each key n in the blank node to quads map:
     #[cfg(feature = "log")]
     let span_ca_3 = debug_span!(
@@ -243,24 +668,38 @@ pub fn canonicalize_core<D: Digest>(
     #[cfg(feature = "log")]
     debug!("with:");
 
-    for (n, _quads) in state.blank_node_to_quads_map.iter() {
-        #[cfg(feature = "log")]
-        debug!(indent = 1, "- identifier: {}", n);
-
-        // 3.1) Create a hash, h_f(n), for n according to the Hash First Degree Quads algorithm.
-        #[cfg(feature = "log")]
-        let span_ca_3_1 = debug_span!("", indent = 1).entered();
-
-        let hash = hash_first_degree_quads::<D>(&state, n).unwrap();
+    let identifiers: Vec<&String> = state.blank_node_to_quads_map.keys().collect();
+
+    // 3.1) Create a hash, h_f(n), for n according to the Hash First Degree Quads algorithm.
+    // Note that h_f(n) is needed for every blank node, even for uniquely identified ones,
+    // since step 4) issues canonical identifiers in the code point order of these hashes
+    // and the Hash Related Blank Node algorithm may feed them into other hashes.
+    // A cheaper non-cryptographic hash therefore cannot stand in for it, even just to
+    // detect uniqueness, without altering the canonical labeling.
+    #[cfg(feature = "parallel")]
+    let hashes = first_degree_hashes_in_parallel::<D>(state, &identifiers)?;
+    #[cfg(not(feature = "parallel"))]
+    let hashes = identifiers
+        .iter()
+        .map(|n| {
+            #[cfg(feature = "log")]
+            debug!(indent = 1, "- identifier: {}", n);
+            #[cfg(feature = "log")]
+            let _span_ca_3_1 = debug_span!("", indent = 1).entered();
 
-        #[cfg(feature = "log")]
-        span_ca_3_1.exit();
+            hash_first_degree_quads::<D>(state, n)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
+    // the hashes are added in the order of the identifiers however they were computed,
+    // so that the lists in the hash to blank nodes map are the same in either case
+    for (n, hash) in identifiers.into_iter().zip(hashes) {
         // 3.2) Add h_f(n) and n to hash to blank nodes map, including repetitions, creating a new entry if necessary.
+        state.first_degree_hash_map.insert(n.clone(), hash.clone());
         state
             .hash_to_blank_node_map
             .entry(hash)
-            .or_insert_with(Vec::<String>::new)
+            .or_default()
             .push(n.clone());
     }
 
@@ -268,7 +707,7 @@ pub fn canonicalize_core<D: Digest>(
     span_ca_3.exit();
 
     // 4) For each hash to identifier list map entry in hash to blank nodes map, code point ordered by hash:
-    // TODO: check if the ordering in `BTreeMap` is actually in **Unicode code point order**
+    // (`String` keys compare by their UTF-8 bytes, which coincides with code point order)
     #[cfg(feature = "log")]    
     let span_ca_4 = debug_span!(
         "ca.4",
@@ -317,7 +756,8 @@ pub fn canonicalize_core<D: Digest>(
     #[cfg(feature = "log")]
     debug!("with:");
 
-    for (_hash, identifier_list) in state.hash_to_blank_node_map.iter() {
+    let total_buckets = state.hash_to_blank_node_map.len();
+    for (buckets_done, (_hash, identifier_list)) in (1..).zip(state.hash_to_blank_node_map.iter()) {
         #[cfg(feature = "log")]
         {
             debug!(indent = 1, "- hash: {}", _hash);
@@ -339,40 +779,35 @@ pub fn canonicalize_core<D: Digest>(
         #[cfg(feature = "log")]
         debug!("with:");
 
-        for n in identifier_list {
-            #[cfg(feature = "log")]
-            debug!(indent = 1, "- identifier: {}", n);
-
-            // 5.2.1) If a canonical identifier has already been issued for n, continue to the next blank node
-            // identifier.
-            if state.canonical_issuer.get(n).is_some() {
-                continue;
-            }
-
-            // 5.2.2) Create temporary issuer, an identifier issuer initialized with the prefix b.
-            let mut temporary_issuer = IdentifierIssuer::new("b");
-
-            // 5.2.3) Use the Issue Identifier algorithm, passing temporary issuer and n, to issue a new temporary
-            // blank node identifier b_n to n.
-            temporary_issuer.issue(n);
-
-            // 5.2.4) Run the Hash N-Degree Quads algorithm, passing the canonicalization state, n for identifier,
-            // and temporary issuer, appending the result to the hash path list.
-            #[cfg(feature = "log")]
-            let span_ca_5_2_4 = debug_span!("", indent = 1).entered();
-
-            let result = hash_n_degree_quads::<D>(
-                &state,
-                n.clone(),
-                &temporary_issuer,
-                &mut hndq_call_counter,
-            )?;
-
-            #[cfg(feature = "log")]
-            span_ca_5_2_4.exit();
-
-            hash_path_list.push(result);
+        // 5.2.1) If a canonical identifier has already been issued for n, continue to the next blank node
+        // identifier.
+        // (canonical identifiers are not issued during this step, so they can be skipped up front)
+        let identifiers: Vec<&String> = identifier_list
+            .iter()
+            .filter(|n| state.canonical_issuer.get(n).is_none())
+            .collect();
+
+        #[cfg(feature = "parallel")]
+        let hash_paths = hash_paths_in_parallel::<D, C>(state, &identifiers, hndq_call_counter);
+        #[cfg(not(feature = "parallel"))]
+        let hash_paths: Result<Vec<_>, _> = identifiers
+            .into_iter()
+            .map(|n| hash_path::<D, C>(state, n, hndq_call_counter))
+            .collect();
+
+        // keep the canonical identifiers issued so far in the error if the call limit is exceeded
+        let mut hash_paths =
+            hash_paths.map_err(|e| with_partial_map(e, &state.canonical_issuer))?;
+
+        // (the traces are taken in the order of the identifiers, however they were computed)
+        if let Some(traces) = &mut state.chosen_path_traces {
+            traces.extend(
+                hash_paths
+                    .iter_mut()
+                    .filter_map(|result| result.trace.take()),
+            );
         }
+        hash_path_list.extend(hash_paths);
 
         #[cfg(feature = "log")]
         span_ca_5_2.exit();
@@ -387,12 +822,12 @@ pub fn canonicalize_core<D: Digest>(
         )
         .entered();
 
-        // TODO: check if the `sort()` here is actually in **Unicode code point order**
+        // (results are ordered by their hash strings, i.e., in code point order)
         hash_path_list.sort();
 
         #[cfg(feature = "log")]
         {
-            fn has_duplicates_in_hash_path_list(l: &Vec<HashNDegreeQuadsResult>) -> bool {
+            fn has_duplicates_in_hash_path_list(l: &[HashNDegreeQuadsResult]) -> bool {
                 if l.is_empty() {
                     return false;
                 }
@@ -431,13 +866,8 @@ pub fn canonicalize_core<D: Digest>(
             #[cfg(feature = "log")]
             let span_ca_5_3_1 = debug_span!("ca.5.3.1", indent = 2).entered();
 
-            // Retrieve the existing identifiers in the order of the temporarily issued identifiers.
-            let temporarily_issued_identifiers_map = &result.issuer.issued_identifiers_map;
-            let inverted_map: BTreeMap<_, _> = temporarily_issued_identifiers_map
-                .iter()
-                .map(|(k, v)| (v, k))
-                .collect();
-            for existing_identifier in inverted_map.into_values() {
+            // The issued identifiers map keeps the existing identifiers in issuance order.
+            for existing_identifier in result.issuer.issued_identifiers_map.keys() {
                 #[cfg(feature = "log")]
                 debug!("- existing identifier: {}", existing_identifier);
 
@@ -453,6 +883,8 @@ pub fn canonicalize_core<D: Digest>(
 
         #[cfg(feature = "log")]
         span_ca_5_3.exit();
+
+        progress(buckets_done, total_buckets);
     }
 
     #[cfg(feature = "log")]
@@ -471,12 +903,21 @@ pub fn canonicalize_core<D: Digest>(
         state.canonical_issuer.serialize_issued_identifiers_map()
     );
     #[cfg(feature = "log")]
-    info!("hndq_call_counter: {:?}", hndq_call_counter);
+    info!(
+        "hndq_call_counter: {{ counter: {}, max_depth: {} }}",
+        hndq_call_counter.sum(),
+        hndq_call_counter.max_depth()
+    );
 
     #[cfg(feature = "log")]
     span_ca_6.exit();
 
-    Ok(state.canonical_issuer.issued_identifiers_map)
+    Ok(state
+        .canonical_issuer
+        .issued_identifiers_map
+        .clone()
+        .into_iter()
+        .collect())
 }
 
 /// **4.6 Hash First Degree Quads**
@@ -512,39 +953,61 @@ fn hash_first_degree_quads<D: Digest>(
         };
 
     // 3) For each quad quad in quads:
-    let mut nquads = quads
-        .iter()
-        .map(|quad| {
-            // 3.1) Serialize the quad in canonical n-quads form with the following special rule:
-            // 3.1.1) If any component in quad is an blank node, then serialize it using a special
-            // identifier as follows:
-            let subject = match &quad.subject {
-                Subject::BlankNode(bnode) => {
-                    Subject::BlankNode(replace_bnid(bnode, reference_blank_node_identifier))
-                }
-                s => s.clone(),
-            };
-            // 3.1.1) If any component in quad is an blank node, then serialize it using a special
-            // identifier as follows:
-            let object = match &quad.object {
-                Term::BlankNode(bnode) => {
-                    Term::BlankNode(replace_bnid(bnode, reference_blank_node_identifier))
-                }
-                s => s.clone(),
-            };
-            // 3.1.1) If any component in quad is an blank node, then serialize it using a special
-            // identifier as follows:
-            let graph_name = match &quad.graph_name {
-                GraphName::BlankNode(bnode) => {
-                    GraphName::BlankNode(replace_bnid(bnode, reference_blank_node_identifier))
-                }
-                s => s.clone(),
-            };
-            let predicate = quad.predicate.clone();
-
-            Quad::new(subject, predicate, object, graph_name).to_string() + " .\n"
-        })
-        .collect::<Vec<String>>();
+    let replaced_quads = quads.map(|quad| {
+        // 3.1) Serialize the quad in canonical n-quads form with the following special rule:
+        // 3.1.1) If any component in quad is an blank node, then serialize it using a special
+        // identifier as follows:
+        let subject = match &quad.subject {
+            Subject::BlankNode(bnode) => {
+                Subject::BlankNode(replace_bnid(bnode, reference_blank_node_identifier))
+            }
+            #[cfg(feature = "rdf-star")]
+            Subject::Triple(triple) => Subject::Triple(Box::new(replace_bnids_in_quoted_triple(
+                triple,
+                reference_blank_node_identifier,
+            ))),
+            s => s.clone(),
+        };
+        // 3.1.1) If any component in quad is an blank node, then serialize it using a special
+        // identifier as follows:
+        let object = match &quad.object {
+            Term::BlankNode(bnode) => {
+                Term::BlankNode(replace_bnid(bnode, reference_blank_node_identifier))
+            }
+            #[cfg(feature = "rdf-star")]
+            Term::Triple(triple) => Term::Triple(Box::new(replace_bnids_in_quoted_triple(
+                triple,
+                reference_blank_node_identifier,
+            ))),
+            s => s.clone(),
+        };
+        // 3.1.1) If any component in quad is an blank node, then serialize it using a special
+        // identifier as follows:
+        let graph_name = match &quad.graph_name {
+            GraphName::BlankNode(bnode) => {
+                GraphName::BlankNode(replace_bnid(bnode, reference_blank_node_identifier))
+            }
+            s => s.clone(),
+        };
+        let predicate = quad.predicate.clone();
+
+        Quad::new(subject, predicate, object, graph_name)
+    });
+    #[cfg(not(feature = "bump-alloc"))]
+    let mut nquads = wiped(
+        replaced_quads
+            .map(|quad| quad.to_string() + " .\n")
+            .collect::<Vec<String>>(),
+    );
+    // (the serializations are allocated from an arena, which is freed at once on return)
+    #[cfg(feature = "bump-alloc")]
+    let arena = bumpalo::Bump::new();
+    #[cfg(feature = "bump-alloc")]
+    let mut nquads = wiped(ArenaNQuads(
+        replaced_quads
+            .map(|quad| bumpalo::format!(in &arena, "{} .\n", quad))
+            .collect(),
+    ));
 
     // 3.1.1.1) If the blank node's existing blank node identifier matches the reference
     // blank node identifier then use the blank node identifier a, otherwise, use the blank
@@ -557,6 +1020,35 @@ fn hash_first_degree_quads<D: Digest>(
         }
     }
 
+    // (the same rule applies to blank nodes in quoted triples, at any depth)
+    #[cfg(feature = "rdf-star")]
+    fn replace_bnids_in_quoted_triple(
+        triple: &Triple,
+        reference_blank_node_identifier: &String,
+    ) -> Triple {
+        let subject = match &triple.subject {
+            Subject::BlankNode(bnode) => {
+                Subject::BlankNode(replace_bnid(bnode, reference_blank_node_identifier))
+            }
+            Subject::Triple(t) => Subject::Triple(Box::new(replace_bnids_in_quoted_triple(
+                t,
+                reference_blank_node_identifier,
+            ))),
+            s => s.clone(),
+        };
+        let object = match &triple.object {
+            Term::BlankNode(bnode) => {
+                Term::BlankNode(replace_bnid(bnode, reference_blank_node_identifier))
+            }
+            Term::Triple(t) => Term::Triple(Box::new(replace_bnids_in_quoted_triple(
+                t,
+                reference_blank_node_identifier,
+            ))),
+            o => o.clone(),
+        };
+        Triple::new(subject, triple.predicate.clone(), object)
+    }
+
     #[cfg(feature = "log")]
     {
         debug!("nquads:");
@@ -566,12 +1058,19 @@ fn hash_first_degree_quads<D: Digest>(
     }
 
     // 4) Sort nquads in Unicode code point order.
-    // TODO: check if `sort()` here is actually sorting in **Unicode code point order**
+    // (`String` comparison is bytewise on UTF-8, which coincides with code point order)
     nquads.sort();
 
     // 5) Return the hash that results from passing the sorted and concatenated
     // nquads through the hash algorithm.
-    let hashed_nquads = hash::<D>(nquads.join(""));
+    let hashed_nquads = hash_concatenated::<D>(&nquads[..]);
+
+    #[cfg(feature = "transcript")]
+    canonicalization_state.record(
+        TranscriptOperation::HashFirstDegreeQuads,
+        &nquads[..],
+        &hashed_nquads,
+    );
 
     #[cfg(feature = "log")]
     debug!("hash: {}", hashed_nquads);
@@ -579,6 +1078,7 @@ fn hash_first_degree_quads<D: Digest>(
     Ok(hashed_nquads)
 }
 
+#[derive(Clone, Copy)]
 enum HashRelatedBlankNodePosition {
     Subject,
     Object,
@@ -594,6 +1094,137 @@ impl HashRelatedBlankNodePosition {
     }
 }
 
+/// Performs steps 5.2.2) to 5.2.4) of the canonicalization algorithm for the blank node identifier n,
+/// returning the result of the Hash N-Degree Quads algorithm to be appended to the hash path list.
+fn hash_path<D: Digest, C: AddHndqCall>(
+    state: &CanonicalizationState,
+    n: &str,
+    hndq_call_counter: &mut C,
+) -> Result<HashNDegreeQuadsResult, CanonicalizationError> {
+    #[cfg(feature = "log")]
+    debug!(indent = 1, "- identifier: {}", n);
+
+    // 5.2.2) Create temporary issuer, an identifier issuer initialized with the prefix b.
+    let mut temporary_issuer = IdentifierIssuer::new("b");
+
+    // 5.2.3) Use the Issue Identifier algorithm, passing temporary issuer and n, to issue a new temporary
+    // blank node identifier b_n to n.
+    temporary_issuer.issue(n);
+
+    // 5.2.4) Run the Hash N-Degree Quads algorithm, passing the canonicalization state, n for identifier,
+    // and temporary issuer, appending the result to the hash path list.
+    #[cfg(feature = "log")]
+    let _span_ca_5_2_4 = debug_span!("", indent = 1).entered();
+
+    hash_n_degree_quads::<D, C>(
+        state,
+        n.to_string(),
+        &temporary_issuer,
+        hndq_call_counter,
+        1,
+    )
+}
+
+/// Performs steps 5.2.2) to 5.2.4) for each of the blank node identifiers in parallel,
+/// returning the results in the same order as the identifiers.
+/// Each run of the Hash N-Degree Quads algorithm only reads the canonicalization state,
+/// so the results, and therefore the canonical labeling, do not depend on the scheduling;
+/// the calls are counted on the shared counter.
+/// The work runs on the current rayon thread pool, which can be configured by the caller
+/// (e.g., with `rayon::ThreadPool::install`).
+/// If a hashing transcript is being recorded, the identifiers are processed sequentially
+/// to keep the transcript in a deterministic order.
+#[cfg(feature = "parallel")]
+fn hash_paths_in_parallel<D: Digest, C: HndqCallCounter + Send>(
+    state: &CanonicalizationState,
+    identifiers: &[&String],
+    hndq_call_counter: &mut C,
+) -> Result<Vec<HashNDegreeQuadsResult>, CanonicalizationError> {
+    #[cfg(feature = "transcript")]
+    if state.transcript.is_some() {
+        return identifiers
+            .iter()
+            .map(|n| hash_path::<D, C>(state, n, hndq_call_counter))
+            .collect();
+    }
+
+    let shared_counter = Mutex::new(hndq_call_counter);
+    identifiers
+        .par_iter()
+        .map(|n| hash_path::<D, _>(state, n, &mut SharedHndqCallCounter(&shared_counter)))
+        .collect()
+}
+
+/// Performs step 3.1) for each of the blank node identifiers in parallel,
+/// returning the first degree hashes in the same order as the identifiers.
+/// The Hash First Degree Quads algorithm only reads the canonicalization state,
+/// so the hashes do not depend on the scheduling.
+/// As in [`hash_paths_in_parallel`], the identifiers are processed sequentially
+/// if a hashing transcript is being recorded.
+#[cfg(feature = "parallel")]
+fn first_degree_hashes_in_parallel<D: Digest>(
+    state: &CanonicalizationState,
+    identifiers: &[&String],
+) -> Result<Vec<String>, CanonicalizationError> {
+    #[cfg(feature = "transcript")]
+    if state.transcript.is_some() {
+        return identifiers
+            .iter()
+            .map(|n| hash_first_degree_quads::<D>(state, n))
+            .collect();
+    }
+
+    identifiers
+        .par_iter()
+        .map(|n| hash_first_degree_quads::<D>(state, n))
+        .collect()
+}
+
+/// Attaches the canonical identifiers issued so far to the error
+/// if the call limit of the Hash N-Degree Quads algorithm has been exceeded
+fn with_partial_map(
+    error: CanonicalizationError,
+    canonical_issuer: &IdentifierIssuer,
+) -> CanonicalizationError {
+    match error {
+        CanonicalizationError::HndqCallLimitExceeded { limit, .. } => {
+            CanonicalizationError::HndqCallLimitExceeded {
+                limit,
+                partial_map: canonical_issuer
+                    .issued_identifiers_map
+                    .clone()
+                    .into_iter()
+                    .collect(),
+            }
+        }
+        e => e,
+    }
+}
+
+/// Counts a call to the Hash N-Degree Quads algorithm at the given recursion depth,
+/// failing if the call exceeds the limit
+trait AddHndqCall {
+    fn add_call(&mut self, identifier: &str, depth: usize) -> Result<(), CanonicalizationError>;
+}
+
+impl<C: HndqCallCounter> AddHndqCall for C {
+    fn add_call(&mut self, identifier: &str, depth: usize) -> Result<(), CanonicalizationError> {
+        self.record_depth(depth);
+        self.add(identifier)
+    }
+}
+
+/// A call counter shared among the threads running the Hash N-Degree Quads algorithm
+#[cfg(feature = "parallel")]
+struct SharedHndqCallCounter<'a, 'b, C>(&'a Mutex<&'b mut C>);
+
+#[cfg(feature = "parallel")]
+impl<C: HndqCallCounter> AddHndqCall for SharedHndqCallCounter<'_, '_, C> {
+    fn add_call(&mut self, identifier: &str, depth: usize) -> Result<(), CanonicalizationError> {
+        self.0.lock().unwrap().add_call(identifier, depth)
+    }
+}
+
 /// **4.7 Hash Related Blank Node**
 ///   This algorithm generates a hash for some blank node component of a quad, considering
 ///   its position within that quad. This is used as part of the Hash N-Degree Quads
@@ -613,11 +1244,11 @@ fn hash_related_blank_node<D: Digest>(
     }
 
     // 1) Initialize a string input to the value of position.
-    let input = match position {
+    let mut input = wiped(match position {
         HashRelatedBlankNodePosition::Graph => position.serialize().to_string(),
         // 2) If position is not g, append <, the value of the predicate in quad, and > to input.
         _ => format!("{}{}", position.serialize(), quad.predicate),
-    };
+    });
 
     // 3) If there is a canonical identifier for related, or an identifier issued by issuer,
     // append the string _:, followed by that identifier (using the canonical identifier if
@@ -632,20 +1263,32 @@ fn hash_related_blank_node<D: Digest>(
             Some(id) => format!("_:{}", id),
             // 4) Otherwise, append the result of the Hash First Degree Quads algorithm,
             // passing related to input.
-            None => hash_first_degree_quads::<D>(state, related)?,
+            // (the result does not change during the algorithm, so the one from step 3 of the
+            //  canonicalization algorithm is reused if available)
+            None => match state.first_degree_hash_map.get(related) {
+                Some(hash) => hash.clone(),
+                None => hash_first_degree_quads::<D>(state, related)?,
+            },
         },
     };
 
     #[cfg(feature = "log")]
     span_hrbn_3.exit();
 
-    let input = format!("{}{}", input, identifier);
+    input.push_str(&identifier);
 
     #[cfg(feature = "log")]
-    debug!(indent = 1, "input: \"{}\"", input);
+    debug!(indent = 1, "input: \"{}\"", input.as_str());
 
     // 5) Return the hash that results from passing input through the hash algorithm.
-    let output = hash::<D>(input);
+    let output = hash::<D>(input.as_str());
+
+    #[cfg(feature = "transcript")]
+    state.record(
+        TranscriptOperation::HashRelatedBlankNode,
+        &[input.as_str()],
+        &output,
+    );
 
     #[cfg(feature = "log")]
     debug!(indent = 1, "hash: {}", output);
@@ -657,11 +1300,13 @@ fn hash_related_blank_node<D: Digest>(
 struct HashNDegreeQuadsResult {
     hash: String,
     issuer: IdentifierIssuer,
+    /// The chosen paths, if requested and called from the canonicalization algorithm
+    trace: Option<ChosenPathTrace>,
 }
 
 impl PartialOrd for HashNDegreeQuadsResult {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.hash.partial_cmp(&other.hash)
+        Some(self.cmp(other))
     }
 }
 
@@ -683,11 +1328,16 @@ impl Ord for HashNDegreeQuadsResult {
 ///   blank node to recursively hash quads for, and path identifier issuer which is an
 ///   identifier issuer that issues temporary blank node identifiers. The output from this
 ///   algorithm will be a hash and the identifier issuer used to help generate it.
-fn hash_n_degree_quads<D: Digest>(
+///
+/// `depth` is the recursion depth of this call, which is 1 for the calls from the
+/// canonicalization algorithm; it is passed along rather than tracked by the counter,
+/// since the calls for different blank nodes may run in parallel.
+fn hash_n_degree_quads<D: Digest, C: AddHndqCall>(
     state: &CanonicalizationState,
     identifier: String,
     path_identifier_issuer: &IdentifierIssuer,
-    call_counter: &mut SimpleHndqCallCounter,
+    call_counter: &mut C,
+    depth: usize,
 ) -> Result<HashNDegreeQuadsResult, CanonicalizationError> {
     #[cfg(feature = "log")]
     let _span_hndq = debug_span!(
@@ -705,7 +1355,7 @@ fn hash_n_degree_quads<D: Digest>(
     }
 
     // Check call limit and halt if necessary to avoid poison input
-    call_counter.add(&identifier)?;
+    call_counter.add_call(&identifier, depth)?;
 
     let mut issuer = path_identifier_issuer.clone();
 
@@ -729,7 +1379,7 @@ fn hash_n_degree_quads<D: Digest>(
     #[cfg(feature = "log")]
     {
         debug!("quads:");
-        for quad in quads {
+        for quad in quads.clone() {
             debug!(indent = 1, "- {}", quad.to_string().trim_end());
         }
     }
@@ -785,9 +1435,7 @@ fn hash_n_degree_quads<D: Digest>(
 
                 // 3.1.2) Add a mapping of hash to the blank node identifier for component to Hn,
                 // adding an entry as necessary.
-                h_n.entry(hash)
-                    .or_insert_with(Vec::<String>::new)
-                    .push(bnode_id);
+                h_n.entry(hash).or_default().push(bnode_id);
             };
         };
         // 3.1) For each component in quad, where component is the subject, object, or graph name,
@@ -816,11 +1464,34 @@ fn hash_n_degree_quads<D: Digest>(
 
                 // 3.1.2) Add a mapping of hash to the blank node identifier for component to Hn,
                 // adding an entry as necessary.
-                h_n.entry(hash)
-                    .or_insert_with(Vec::<String>::new)
-                    .push(bnode_id);
+                h_n.entry(hash).or_default().push(bnode_id);
             };
         };
+        // (blank nodes in a quoted triple in the subject or object are related as well,
+        //  and are hashed with the position of the quoted triple)
+        #[cfg(feature = "rdf-star")]
+        for (triple, position) in [
+            match &quad.subject {
+                Subject::Triple(t) => Some((t, HashRelatedBlankNodePosition::Subject)),
+                _ => None,
+            },
+            match &quad.object {
+                Term::Triple(t) => Some((t, HashRelatedBlankNodePosition::Object)),
+                _ => None,
+            },
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for bnode_id in quoted_blank_node_identifiers(triple) {
+                if bnode_id != identifier {
+                    let bnode_id = bnode_id.to_string();
+                    let hash =
+                        hash_related_blank_node::<D>(state, &bnode_id, quad, &issuer, position)?;
+                    h_n.entry(hash).or_default().push(bnode_id);
+                }
+            }
+        }
         // 3.1) For each component in quad, where component is the subject, object, or graph name,
         // and it is a blank node that is not identified by identifier:
         if let GraphName::BlankNode(bnode) = &quad.graph_name {
@@ -846,9 +1517,7 @@ fn hash_n_degree_quads<D: Digest>(
 
                 // 3.1.2) Add a mapping of hash to the blank node identifier for component to Hn,
                 // adding an entry as necessary.
-                h_n.entry(hash)
-                    .or_insert_with(Vec::<String>::new)
-                    .push(bnode_id);
+                h_n.entry(hash).or_default().push(bnode_id);
             };
         };
 
@@ -870,10 +1539,10 @@ fn hash_n_degree_quads<D: Digest>(
     span_hndq_3.exit();
 
     // 4) Create an empty string, data to hash.
-    let mut data_to_hash = Vec::<String>::new();
+    let mut data_to_hash = wiped(Vec::<String>::new());
 
     // 5) For each related hash to blank node list mapping in Hn, code point ordered by related hash:
-    // TODO: check if keys in BTreeMap is actually sorted in **code point order**
+    // (`String` keys compare by their UTF-8 bytes, which coincides with code point order)
 
     #[cfg(feature = "log")]
     let span_hndq_5 = debug_span!(
@@ -898,7 +1567,11 @@ fn hash_n_degree_quads<D: Digest>(
         let mut chosen_path = String::new();
 
         // 5.3) Create an unset chosen issuer variable.
-        let mut chosen_issuer = IdentifierIssuer::new("UNSET");
+        // (rather than copying issuer for every permutation, issuer itself serves as issuer
+        //  copy and is rolled back to this checkpoint before each permutation, keeping only
+        //  the identifiers issued for the chosen path as the chosen issuer)
+        let checkpoint = issuer.checkpoint();
+        let mut chosen_issued_identifiers = Vec::<(String, String)>::new();
 
         // 5.4) For each permutation p of blank node list:
 
@@ -913,7 +1586,13 @@ fn hash_n_degree_quads<D: Digest>(
         #[cfg(feature = "log")]
         let mut span_hndq_5_4_flag = false;
 
-        'perm_loop: for p in blank_node_list.iter().permutations(blank_node_list.len()) {
+        // (the permutations are generated in lexicographic order, so that all of those
+        //  sharing a prefix whose path already exceeds chosen path can be skipped at once;
+        //  each of them would be skipped at the same related in 5.4.4.3 anyway, before any
+        //  recursion, so neither the result nor the number of calls changes)
+        let mut permutations = LexicographicPermutations::new(blank_node_list.len());
+        'perm_loop: while let Some(indices) = permutations.next() {
+            let p: Vec<&String> = indices.iter().map(|&i| &blank_node_list[i]).collect();
             #[cfg(feature = "log")]
             {
                 if !span_hndq_5_4_flag {
@@ -924,10 +1603,13 @@ fn hash_n_degree_quads<D: Digest>(
             }
 
             // 5.4.1) Create a copy of issuer, issuer copy.
-            let mut issuer_copy = issuer.clone();
+            issuer.rollback(checkpoint);
+            let issuer_copy = &mut issuer;
 
             // 5.4.2) Create a string path.
-            let mut path_vec = Vec::<String>::new();
+            // The path is extended in place rather than joined from its segments
+            // on every check, which would be quadratic in the length of the path.
+            let mut path = String::new();
 
             // 5.4.3) Create a recursion list, to store blank node identifiers that must be
             // recursively processed by this algorithm.
@@ -944,7 +1626,7 @@ fn hash_n_degree_quads<D: Digest>(
             #[cfg(feature = "log")]
             debug!("with:");
 
-            for related in p {
+            for (position, related) in p.into_iter().enumerate() {
                 #[cfg(feature = "log")]
                 debug!(indent = 1, "- related: {}", related);
 
@@ -952,7 +1634,8 @@ fn hash_n_degree_quads<D: Digest>(
                     // 5.4.4.1) If a canonical identifier has been issued for related by
                     // canonical issuer, append the string _:, followed by the canonical
                     // identifier for related, to path.
-                    path_vec.push(format!("_:{}", canonical_identifier));
+                    path.push_str("_:");
+                    path.push_str(&canonical_identifier);
                 } else {
                     // 5.4.4.2) Otherwise:
                     // 5.4.4.2.1) If issuer copy has not issued an identifier for
@@ -963,20 +1646,21 @@ fn hash_n_degree_quads<D: Digest>(
                     // 5.4.4.2.2) Use the Issue Identifier algorithm, passing issuer
                     // copy and related, and append the string _:, followed by the result,
                     // to path.
-                    path_vec.push(format!("_:{}", issuer_copy.issue(related)));
+                    path.push_str("_:");
+                    path.push_str(&issuer_copy.issue(related));
                 }
 
                 // 5.4.4.3) If chosen path is not empty and the length of path is greater
                 // than or equal to the length of chosen path and path is greater than
                 // chosen path when considering code point order, then skip to the next
                 // permutation p.
-                let path = path_vec.join("");
 
                 #[cfg(feature = "log")]
                 debug!(indent = 2, "path: \"{}\"", path);
 
                 if !chosen_path.is_empty() && path.len() >= chosen_path.len() && path >= chosen_path
                 {
+                    permutations.skip_prefix(position + 1);
                     continue 'perm_loop;
                 }
             }
@@ -1013,20 +1697,26 @@ fn hash_n_degree_quads<D: Digest>(
                 #[cfg(feature = "log")]
                 let span_hndq_5_4_5_1 = debug_span!("", indent = 1).entered();
 
-                let result =
-                    hash_n_degree_quads::<D>(state, related.clone(), &issuer_copy, call_counter)?;
+                let result = hash_n_degree_quads::<D, C>(
+                    state,
+                    related.clone(),
+                    issuer_copy,
+                    call_counter,
+                    depth + 1,
+                )?;
 
                 #[cfg(feature = "log")]
                 span_hndq_5_4_5_1.exit();
 
                 // 5.4.5.2) Use the Issue Identifier algorithm, passing issuer copy and
                 // related; append the string _:, followed by the result, to path.
-                path_vec.push(format!("_:{}", issuer_copy.issue(related)));
+                path.push_str("_:");
+                path.push_str(&issuer_copy.issue(related));
 
                 // 5.4.5.3) Append <, the hash in result, and > to path.
-                path_vec.push("<".to_string());
-                path_vec.push(result.hash);
-                path_vec.push(">".to_string());
+                path.push('<');
+                path.push_str(&result.hash);
+                path.push('>');
 
                 // 5.4.5.4) Set issuer copy to the identifier issuer in result.
 
@@ -1037,8 +1727,7 @@ fn hash_n_degree_quads<D: Digest>(
                     indent = 2
                 ).entered();
 
-                issuer_copy = result.issuer;
-                let path = path_vec.join("");
+                *issuer_copy = result.issuer;
 
                 #[cfg(feature = "log")]
                 {
@@ -1066,10 +1755,9 @@ fn hash_n_degree_quads<D: Digest>(
             // 5.4.6) If chosen path is empty or path is less than chosen path when
             // considering code point order, set chosen path to path and chosen issuer to
             // issuer copy.
-            let path = path_vec.join("");
             if chosen_path.is_empty() || path < chosen_path {
                 chosen_path = path;
-                chosen_issuer = issuer_copy;
+                chosen_issued_identifiers = issuer_copy.issued_since(checkpoint);
             }
         }
 
@@ -1096,7 +1784,8 @@ fn hash_n_degree_quads<D: Digest>(
         span_hndq_5_5.exit();
 
         // 5.6) Replace issuer, by reference, with chosen issuer.
-        issuer = chosen_issuer;
+        issuer.rollback(checkpoint);
+        issuer.reissue(chosen_issued_identifiers);
     }
 
     #[cfg(feature = "log")]
@@ -1112,7 +1801,14 @@ fn hash_n_degree_quads<D: Digest>(
     )
     .entered();
 
-    let hash = hash::<D>(data_to_hash.join(""));
+    let hash = hash_concatenated::<D>(&data_to_hash[..]);
+
+    #[cfg(feature = "transcript")]
+    state.record(
+        TranscriptOperation::HashNDegreeQuads,
+        &data_to_hash[..],
+        &hash,
+    );
 
     #[cfg(feature = "log")]
     {
@@ -1122,7 +1818,40 @@ fn hash_n_degree_quads<D: Digest>(
     #[cfg(feature = "log")]
     span_hndq_6.exit();
 
-    Ok(HashNDegreeQuadsResult { hash, issuer })
+    // (the data to hash consists of the related hashes each followed by its chosen path)
+    let trace = (depth == 1 && state.chosen_path_traces.is_some()).then(|| ChosenPathTrace {
+        identifier: identifier.clone(),
+        chosen_paths: data_to_hash
+            .chunks(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect(),
+        issued_identifiers: issuer
+            .issued_identifiers_map
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        hash: hash.clone(),
+    });
+
+    Ok(HashNDegreeQuadsResult {
+        hash,
+        issuer,
+        trace,
+    })
+}
+
+/// Returns the result of the Hash First Degree Quads algorithm for every
+/// blank node in the input dataset, keyed by blank node identifier.
+pub(crate) fn first_degree_hashes<D: Digest>(
+    input_dataset: &Dataset,
+) -> Result<BTreeMap<String, String>, CanonicalizationError> {
+    let mut state = CanonicalizationState::new();
+    state.update_blank_node_to_quads_map(input_dataset);
+    state
+        .blank_node_to_quads_map
+        .keys()
+        .map(|n| Ok((n.clone(), hash_first_degree_quads::<D>(&state, n)?)))
+        .collect()
 }
 
 /// **5. Serialization**
@@ -1138,14 +1867,49 @@ fn hash_n_degree_quads<D: Digest>(
 ///   serialized using the canonical label associated with each blank node from the issued
 ///   identifiers map component of the canonicalized dataset.
 pub fn serialize(dataset: &Dataset) -> String {
-    let mut ordered_dataset: Vec<QuadRef> = dataset.iter().collect();
-    ordered_dataset.sort_by_cached_key(|q| q.to_string());
-    ordered_dataset
-        .iter()
-        .map(|q| q.to_string() + " .\n")
-        .collect()
+    let mut buffer = Vec::new();
+    serialize_to_writer(dataset, &mut buffer).expect("writing to a Vec<u8> never fails");
+    String::from_utf8(buffer).expect("N-Quads statements are valid UTF-8")
+}
+
+/// Writes the serialized canonical form of the dataset to the writer as [`serialize`] does,
+/// and flushes the writer at the end.
+/// The statements are serialized and sorted in memory first, since their order is only known
+/// once all of them are, and then written in one pass.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{serialize, serialize_to_writer};
+///
+/// let input = b"<urn:ex:s> <urn:ex:p> <urn:ex:o2> .\n<urn:ex:s> <urn:ex:p> <urn:ex:o1> .\n";
+/// let dataset: Dataset = NQuadsParser::new()
+///     .for_slice(input)
+///     .map(|q| q.unwrap())
+///     .collect();
+/// let mut output = Vec::new();
+/// serialize_to_writer(&dataset, &mut output).unwrap();
+///
+/// assert_eq!(String::from_utf8(output).unwrap(), serialize(&dataset));
+/// ```
+pub fn serialize_to_writer<W: Write>(dataset: &Dataset, writer: &mut W) -> io::Result<()> {
+    let mut statements: Vec<String> = dataset.iter().map(|q| q.to_string()).collect();
+    // oxrdf does not provide an ordering of quads that matches the code point order of
+    // their N-Quads forms, so we sort by the serialized strings themselves.
+    // Quads in the default graph are displayed without a graph component,
+    // so a default-graph-only dataset serializes exactly like `serialize_graph`
+    statements.sort_unstable();
+    for statement in statements {
+        writeln!(writer, "{} .", statement)?;
+    }
+    writer.flush()
 }
 
+/// Serializes the graph as an N-Triples document in the same way as [`serialize`] does
+/// for datasets, i.e., one `subject predicate object .` statement per line without any
+/// graph name, sorted into code point order.
 pub fn serialize_graph(graph: &Graph) -> String {
     let mut ordered_graph: Vec<TripleRef> = graph.iter().collect();
     ordered_graph.sort_by_cached_key(|t| t.to_string());
@@ -1157,8 +1921,12 @@ pub fn serialize_graph(graph: &Graph) -> String {
 
 #[cfg(test)]
 mod tests {
-    use oxrdf::{BlankNode, NamedNode, NamedNodeRef};
+    use crate::counter::{PerNodeHndqCallCounter, SimpleHndqCallCounter};
+    #[cfg(not(feature = "log"))]
+    use itertools::Itertools;
+    use oxrdf::{BlankNode, NamedNode, NamedNodeRef, QuadRef};
     use sha2::Sha256;
+    use std::collections::BTreeSet;
 
     use super::*;
 
@@ -1175,6 +1943,138 @@ mod tests {
         assert_eq!(canonical_issuer.issue("b0"), "c14n0".to_string());
     }
 
+    #[test]
+    fn test_quads_stored_once() {
+        let p = NamedNodeRef::new("http://example.org/vocab#p").unwrap();
+        let q = NamedNodeRef::new("http://example.org/vocab#q").unwrap();
+        let mut dataset = Dataset::new();
+        dataset.insert(QuadRef::new(
+            &BlankNode::new("e0").unwrap(),
+            p,
+            &BlankNode::new("e1").unwrap(),
+            &BlankNode::new("g").unwrap(),
+        ));
+        dataset.insert(QuadRef::new(
+            &BlankNode::new("e1").unwrap(),
+            q,
+            p,
+            GraphNameRef::DefaultGraph,
+        ));
+        dataset.insert(QuadRef::new(p, q, p, GraphNameRef::DefaultGraph));
+
+        let mut state = CanonicalizationState::new();
+        state.update_blank_node_to_quads_map(&dataset);
+
+        // only quads with blank nodes are kept, each once
+        assert_eq!(state.quads.len(), 2);
+        assert_eq!(state.blank_node_to_quads_map.len(), 3);
+        let quads_for = |n: &str| {
+            state
+                .get_quads_for_blank_node(&n.to_string())
+                .unwrap()
+                .map(|quad| quad.to_string())
+                .collect::<BTreeSet<_>>()
+        };
+        assert_eq!(quads_for("e0"), quads_for("g"));
+        assert_eq!(quads_for("e1").len(), 2);
+        assert!(quads_for("e1").is_superset(&quads_for("e0")));
+    }
+
+    #[test]
+    fn test_issuer_rollback() {
+        let mut issuer = IdentifierIssuer::new("b");
+        issuer.issue("e0");
+        let before = issuer.clone();
+        let checkpoint = issuer.checkpoint();
+
+        issuer.issue("e1");
+        issuer.issue("e0");
+        issuer.issue("e2");
+        let after = issuer.clone();
+        let issued = issuer.issued_since(checkpoint);
+        assert_eq!(
+            issued,
+            vec![
+                ("e1".to_string(), "b1".to_string()),
+                ("e2".to_string(), "b2".to_string())
+            ]
+        );
+
+        // rolling back restores the issuer exactly, as if it had been copied
+        issuer.rollback(checkpoint);
+        assert_eq!(issuer, before);
+        assert_eq!(issuer.issue("e2"), "b1");
+        issuer.rollback(checkpoint);
+
+        issuer.reissue(issued);
+        assert_eq!(issuer, after);
+        assert_eq!(issuer.issue("e3"), "b3");
+    }
+
+    #[test]
+    fn test_lexicographic_permutations() {
+        for n in 0..6 {
+            let mut permutations = LexicographicPermutations::new(n);
+            let mut generated = Vec::new();
+            while let Some(p) = permutations.next() {
+                generated.push(p.to_vec());
+            }
+            let expected: Vec<Vec<usize>> = (0..n).permutations(n).collect();
+            assert_eq!(generated, expected);
+        }
+
+        // skipping the permutations starting with [1, 0] (and then [2])
+        let mut permutations = LexicographicPermutations::new(3);
+        let mut generated = Vec::new();
+        while let Some(p) = permutations.next() {
+            generated.push(p.to_vec());
+            match p {
+                [1, 0, _] => permutations.skip_prefix(2),
+                [2, ..] => permutations.skip_prefix(1),
+                _ => {}
+            }
+        }
+        assert_eq!(
+            generated,
+            vec![
+                vec![0, 1, 2],
+                vec![0, 2, 1],
+                vec![1, 0, 2],
+                vec![1, 2, 0],
+                vec![2, 0, 1]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hash_concatenated() {
+        // pieces of pseudo-random lengths and contents, including empty and multibyte ones
+        let mut seed: u64 = 2015;
+        let mut next = |bound: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % bound
+        };
+        for _ in 0..100 {
+            let pieces: Vec<String> = (0..next(20))
+                .map(|_| {
+                    (0..next(40))
+                        .map(|_| ['a', 'z', '_', ':', ' ', '\n', 'é', '𝄞'][next(8) as usize])
+                        .collect()
+                })
+                .collect();
+            assert_eq!(
+                hash_concatenated::<Sha256>(&pieces),
+                hash::<Sha256>(pieces.concat())
+            );
+            assert_eq!(
+                hash_concatenated::<sha2::Sha384>(&pieces),
+                hash::<sha2::Sha384>(pieces.concat())
+            );
+        }
+    }
+
     #[test]
     fn test_hash_first_degree_quads_unique_hashes() {
         let mut state = CanonicalizationState::new();
@@ -1380,7 +2280,7 @@ mod tests {
             state
                 .hash_to_blank_node_map
                 .entry(hash)
-                .or_insert_with(Vec::<String>::new)
+                .or_default()
                 .push(n.clone());
         }
 
@@ -1404,11 +2304,12 @@ mod tests {
                 let mut temporary_issuer = IdentifierIssuer::new("b");
                 temporary_issuer.issue(n);
                 let mut hndq_call_counter = SimpleHndqCallCounter::default();
-                let result = hash_n_degree_quads::<Sha256>(
+                let result = hash_n_degree_quads::<Sha256, _>(
                     &state,
                     n.clone(),
                     &temporary_issuer,
                     &mut hndq_call_counter,
+                    1,
                 )
                 .unwrap();
                 hash_path_list.push(result);
@@ -1424,4 +2325,892 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_serialize_code_point_order() {
+        // U+FF61 precedes U+1F303 in code point order, whereas UTF-16 code unit order
+        // (0xFF61 vs. 0xD83C) would put them the other way around
+        let input = r#"<http://example.com/🌃> <http://example.com/p> "a" .
+<http://example.com/｡> <http://example.com/p> "a" .
+<http://example.com/a> <http://example.com/p> _:b0 .
+<http://example.com/a> <http://example.com/p> <http://example.com/o> .
+<http://example.com/a> <http://example.com/p> "o" .
+<http://example.com/a> <http://example.com/p> "o"@en .
+<http://example.com/a> <http://example.com/p> "o" <http://example.com/g> .
+"#;
+        let expected = r#"<http://example.com/a> <http://example.com/p> "o" .
+<http://example.com/a> <http://example.com/p> "o" <http://example.com/g> .
+<http://example.com/a> <http://example.com/p> "o"@en .
+<http://example.com/a> <http://example.com/p> <http://example.com/o> .
+<http://example.com/a> <http://example.com/p> _:b0 .
+<http://example.com/｡> <http://example.com/p> "a" .
+<http://example.com/🌃> <http://example.com/p> "a" .
+"#;
+        let dataset: Dataset = oxttl::NQuadsParser::new()
+            .for_reader(input.as_bytes())
+            .map(|q| q.unwrap())
+            .collect();
+        assert_eq!(serialize(&dataset), expected);
+
+        let sorted: String = crate::api::sort(&dataset)
+            .iter()
+            .map(|q| q.to_string() + " .\n")
+            .collect();
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn test_graph_name_also_used_as_subject() {
+        let input = r#"_:g <http://example.com/#p> "graph" .
+_:g <http://example.com/#q> _:e0 .
+_:e0 <http://example.com/#r> _:e1 _:g .
+_:e1 <http://example.com/#r> _:e0 _:g .
+"#;
+        let expected = r#"_:c14n0 <http://example.com/#r> _:c14n2 _:c14n1 .
+_:c14n1 <http://example.com/#p> "graph" .
+_:c14n1 <http://example.com/#q> _:c14n2 .
+_:c14n2 <http://example.com/#r> _:c14n0 _:c14n1 .
+"#;
+        let dataset: Dataset = oxttl::NQuadsParser::new()
+            .for_reader(input.as_bytes())
+            .map(|q| q.unwrap())
+            .collect();
+
+        let issued_identifiers_map =
+            canonicalize_core::<Sha256, _>(&dataset, SimpleHndqCallCounter::default()).unwrap();
+        assert_eq!(issued_identifiers_map.len(), 3);
+
+        let relabeled = crate::api::relabel(&dataset, &issued_identifiers_map).unwrap();
+        let canonical_g = BlankNode::new(&issued_identifiers_map["g"]).unwrap();
+        for quad in relabeled.iter() {
+            match quad.graph_name {
+                GraphNameRef::DefaultGraph => {
+                    assert_eq!(quad.subject, canonical_g.as_ref().into())
+                }
+                graph_name => assert_eq!(graph_name, canonical_g.as_ref().into()),
+            }
+        }
+        assert_eq!(serialize(&relabeled), expected);
+    }
+
+    #[test]
+    fn test_hndq_call_counters() {
+        // three blank nodes sharing their first degree hash, each requiring
+        // recursive calls of the Hash N-Degree Quads algorithm
+        let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
+_:e0 <http://example.org/vocab#prev> _:e2 _:g .
+_:e1 <http://example.org/vocab#next> _:e2 _:g .
+_:e1 <http://example.org/vocab#prev> _:e0 _:g .
+_:e2 <http://example.org/vocab#next> _:e0 _:g .
+_:e2 <http://example.org/vocab#prev> _:e1 _:g .
+"#;
+        let dataset: Dataset = oxttl::NQuadsParser::new()
+            .for_reader(input.as_bytes())
+            .map(|q| q.unwrap())
+            .collect();
+
+        let mut counter = SimpleHndqCallCounter::new(None);
+        let mut state = CanonicalizationState::new();
+        canonicalize_with_state::<Sha256, _>(&mut state, &dataset, &mut counter).unwrap();
+        let total_calls = counter.sum();
+        assert!(total_calls > 3);
+
+        // the simple counter limits the total number of calls
+        let passes_simple = |limit| {
+            canonicalize_core::<Sha256, _>(&dataset, SimpleHndqCallCounter::new(Some(limit)))
+                .is_ok()
+        };
+        assert!(passes_simple(total_calls));
+        assert!(!passes_simple(total_calls - 1));
+
+        // the per-node counter limits the number of calls for any single blank node,
+        // so it accepts this dataset with a limit under which the simple counter fails
+        let passes_per_node = |limit| {
+            canonicalize_core::<Sha256, _>(&dataset, PerNodeHndqCallCounter::new(Some(limit)))
+                .is_ok()
+        };
+        let per_node_threshold = (1..=total_calls).find(|&l| passes_per_node(l)).unwrap();
+        assert!(per_node_threshold < total_calls);
+        assert!(!passes_simple(per_node_threshold));
+        assert!(!passes_per_node(per_node_threshold - 1));
+
+        // with equal limits, the per-node counter is never stricter than the simple one,
+        // since no single blank node can be hashed more often than all of them together;
+        // in exchange, it allows up to `limit` calls per blank node in total
+        for limit in 1..=total_calls {
+            assert!(!passes_simple(limit) || passes_per_node(limit));
+        }
+    }
+
+    #[cfg(feature = "transcript")]
+    #[test]
+    fn test_transcript() {
+        let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
+_:e0 <http://example.org/vocab#prev> _:e2 _:g .
+_:e1 <http://example.org/vocab#next> _:e2 _:g .
+_:e1 <http://example.org/vocab#prev> _:e0 _:g .
+_:e2 <http://example.org/vocab#next> _:e0 _:g .
+_:e2 <http://example.org/vocab#prev> _:e1 _:g .
+"#;
+        let dataset: Dataset = oxttl::NQuadsParser::new()
+            .for_reader(input.as_bytes())
+            .map(|q| q.unwrap())
+            .collect();
+
+        let mut recording = Recording {
+            transcript: Some(Vec::new()),
+            ..Default::default()
+        };
+        let (canonical_issuer, _) = canonicalize_core_with_recording::<Sha256, _>(
+            &dataset,
+            SimpleHndqCallCounter::default(),
+            &|_, _| {},
+            &mut recording,
+        )
+        .unwrap();
+        let transcript = recording.transcript.unwrap();
+        assert_eq!(
+            HashMap::from_iter(canonical_issuer.into_issued_identifiers_map()),
+            canonicalize_core::<Sha256, _>(&dataset, SimpleHndqCallCounter::default()).unwrap()
+        );
+
+        // first degree hashes come first, one per blank node
+        assert!(transcript[..4]
+            .iter()
+            .all(|e| e.operation == TranscriptOperation::HashFirstDegreeQuads));
+        assert!(transcript
+            .iter()
+            .any(|e| e.operation == TranscriptOperation::HashRelatedBlankNode));
+        assert_eq!(
+            transcript.last().unwrap().operation,
+            TranscriptOperation::HashNDegreeQuads
+        );
+
+        // every entry can be replayed
+        for entry in transcript.iter() {
+            assert_eq!(hash::<Sha256>(&entry.input), entry.output);
+        }
+    }
+
+    /// The hashes computed in each step, as recorded in the logs of the test suite supplement
+    #[cfg(feature = "transcript")]
+    #[derive(Debug, Default, PartialEq, Eq)]
+    struct IntermediateHashes {
+        first_degree: BTreeSet<String>,
+        related: BTreeSet<(String, String)>,
+        n_degree: BTreeSet<String>,
+    }
+
+    #[cfg(feature = "transcript")]
+    impl IntermediateHashes {
+        fn from_log(log: &str) -> Self {
+            let lines: Vec<&str> = log.lines().collect();
+            let indent = |line: &str| line.len() - line.trim_start().len();
+            // the hash at the same level as the log point, before the block ends
+            let block_hash = |i: usize| {
+                lines[i + 1..]
+                    .iter()
+                    .take_while(|l| indent(l) >= indent(lines[i]))
+                    .filter(|l| indent(l) == indent(lines[i]))
+                    .find_map(|l| l.trim_start().strip_prefix("hash: "))
+                    .unwrap()
+                    .to_string()
+            };
+
+            let mut hashes = Self::default();
+            for (i, line) in lines.iter().enumerate() {
+                let trimmed = line.trim_start();
+                if trimmed.starts_with("log point: Hash First Degree Quads function") {
+                    hashes.first_degree.insert(block_hash(i));
+                } else if trimmed.starts_with("log point: Leaving Hash N-Degree Quads function") {
+                    hashes.n_degree.insert(block_hash(i));
+                } else if let Some(input) = trimmed.strip_prefix("input: ") {
+                    let hash = lines[i + 1].trim_start().strip_prefix("hash: ").unwrap();
+                    hashes
+                        .related
+                        .insert((input.trim_matches('"').to_string(), hash.to_string()));
+                }
+            }
+            hashes
+        }
+
+        fn from_transcript(transcript: &[TranscriptEntry]) -> Self {
+            let mut hashes = Self::default();
+            for entry in transcript {
+                let output = entry.output.clone();
+                match entry.operation {
+                    TranscriptOperation::HashFirstDegreeQuads => {
+                        hashes.first_degree.insert(output);
+                    }
+                    TranscriptOperation::HashRelatedBlankNode => {
+                        let input = String::from_utf8(entry.input.clone()).unwrap();
+                        hashes.related.insert((input, output));
+                    }
+                    TranscriptOperation::HashNDegreeQuads => {
+                        hashes.n_degree.insert(output);
+                    }
+                }
+            }
+            hashes
+        }
+    }
+
+    #[cfg(feature = "transcript")]
+    #[test]
+    fn test_supplement_intermediate_hashes() {
+        // logs of the test suite supplement, listing the hashes computed in each step
+        const SUPPLEMENT_LOGS: [&str; 5] = ["test024", "test044", "test047", "test054", "test058"];
+
+        for name in SUPPLEMENT_LOGS {
+            let log = std::fs::read_to_string(format!("tests/rdfc10_supplement/{}.log.yml", name))
+                .unwrap();
+            let input = std::fs::read(format!("tests/rdfc10/{}-in.nq", name)).unwrap();
+            let dataset: Dataset = oxttl::NQuadsParser::new()
+                .for_slice(&input)
+                .map(|q| q.unwrap())
+                .collect();
+
+            let mut recording = Recording {
+                transcript: Some(Vec::new()),
+                ..Default::default()
+            };
+            canonicalize_core_with_recording::<Sha256, _>(
+                &dataset,
+                SimpleHndqCallCounter::default(),
+                &|_, _| {},
+                &mut recording,
+            )
+            .unwrap();
+            let transcript = recording.transcript.unwrap();
+
+            let expected = IntermediateHashes::from_log(&log);
+            assert!(!expected.first_degree.is_empty(), "{}", name);
+            assert_eq!(
+                IntermediateHashes::from_transcript(&transcript),
+                expected,
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_hndq_call_limit_partial_map() {
+        // _:u is labeled in step 4), before the cycle requires any call of the
+        // Hash N-Degree Quads algorithm in step 5)
+        let input = r#"_:u <http://example.org/vocab#label> "unique" .
+_:e0 <http://example.org/vocab#next> _:e1 .
+_:e1 <http://example.org/vocab#next> _:e2 .
+_:e2 <http://example.org/vocab#next> _:e0 .
+"#;
+        let dataset: Dataset = oxttl::NQuadsParser::new()
+            .for_reader(input.as_bytes())
+            .map(|q| q.unwrap())
+            .collect();
+
+        let result = canonicalize_core::<Sha256, _>(&dataset, SimpleHndqCallCounter::new(Some(1)));
+        match result {
+            Err(CanonicalizationError::HndqCallLimitExceeded { limit, partial_map }) => {
+                assert_eq!(limit, 1);
+                assert_eq!(
+                    partial_map,
+                    HashMap::from([("u".to_string(), "c14n0".to_string())])
+                );
+            }
+            _ => panic!("the call limit should be exceeded"),
+        }
+    }
+
+    #[test]
+    fn test_hash_with_selected_digest() {
+        use sha2::Sha384;
+
+        // FIPS 180-2 test vectors for "abc"
+        assert_eq!(
+            hash::<Sha256>("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hash::<Sha384>("abc"),
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed\
+             8086072ba1e7cc2358baeca134c825a7"
+        );
+
+        let parse = |input: &[u8]| -> Dataset {
+            oxttl::NQuadsParser::new()
+                .for_slice(input)
+                .map(|q| q.unwrap())
+                .collect()
+        };
+        let issue_sha256 = |dataset: &Dataset| {
+            canonicalize_core::<Sha256, _>(dataset, SimpleHndqCallCounter::default()).unwrap()
+        };
+        let issue_sha384 = |dataset: &Dataset| {
+            canonicalize_core::<Sha384, _>(dataset, SimpleHndqCallCounter::default()).unwrap()
+        };
+
+        // the cyclic example yields different labels depending on the digest
+        let cycle = parse(
+            br#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
+_:e0 <http://example.org/vocab#prev> _:e2 _:g .
+_:e1 <http://example.org/vocab#next> _:e2 _:g .
+_:e1 <http://example.org/vocab#prev> _:e0 _:g .
+_:e2 <http://example.org/vocab#next> _:e0 _:g .
+_:e2 <http://example.org/vocab#prev> _:e1 _:g .
+"#,
+        );
+        assert_ne!(issue_sha256(&cycle), issue_sha384(&cycle));
+
+        // the W3C test vectors for the same input with SHA-256 (test020) and SHA-384 (test075)
+        let diamond = parse(&std::fs::read("tests/rdfc10/test020-in.nq").unwrap());
+        let expected_map = |path: &str| -> HashMap<String, String> {
+            serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+        };
+        let sha256_map = issue_sha256(&diamond);
+        let sha384_map = issue_sha384(&diamond);
+        assert_eq!(
+            sha256_map,
+            expected_map("tests/rdfc10/test020-rdfc10map.json")
+        );
+        assert_eq!(
+            sha384_map,
+            expected_map("tests/rdfc10/test075-rdfc10map.json")
+        );
+        assert_ne!(sha256_map, sha384_map);
+    }
+
+    #[test]
+    fn test_hash_long_digests() {
+        use sha2::{Sha384, Sha512};
+
+        // the whole digest is hex-encoded whatever its length
+        let sha384 = hash::<Sha384>("");
+        assert_eq!(sha384.len(), 48 * 2);
+        assert_eq!(sha384, base16ct::lower::encode_string(&Sha384::digest("")));
+
+        let sha512 = hash::<Sha512>("abc");
+        assert_eq!(sha512.len(), 64 * 2);
+        assert_eq!(
+            sha512,
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+    }
+
+    #[cfg(feature = "transcript")]
+    #[test]
+    fn test_first_degree_hashes_computed_once() {
+        // a complete directed graph of blank nodes, where the Hash Related Blank Node
+        // algorithm sees every blank node as a related one in every permutation
+        let n = 5;
+        let p = NamedNode::new("http://example.org/vocab#p").unwrap();
+        let mut dataset = Dataset::new();
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                dataset.insert(&Quad::new(
+                    BlankNode::new(format!("e{}", i)).unwrap(),
+                    p.clone(),
+                    BlankNode::new(format!("e{}", j)).unwrap(),
+                    GraphName::DefaultGraph,
+                ));
+            }
+        }
+
+        let mut counter = SimpleHndqCallCounter::new(Some(usize::MAX));
+        let mut state = CanonicalizationState::new();
+        state.transcript = Some(Mutex::default());
+        let issued_identifiers_map =
+            canonicalize_with_state::<Sha256, _>(&mut state, &dataset, &mut counter).unwrap();
+        assert_eq!(issued_identifiers_map.len(), n);
+        assert!(counter.sum() > n);
+        let transcript = state.transcript.unwrap().into_inner().unwrap();
+
+        let count = |operation: TranscriptOperation| {
+            transcript
+                .iter()
+                .filter(|e| e.operation == operation)
+                .count()
+        };
+        assert_eq!(count(TranscriptOperation::HashFirstDegreeQuads), n);
+        assert!(count(TranscriptOperation::HashRelatedBlankNode) > n);
+    }
+
+    #[test]
+    fn test_complete_bipartite_worst_case() {
+        // a complete bipartite graph K_{n,n} of blank nodes linked in both directions,
+        // where every blank node has the same first degree hash
+        let complete_bipartite = |n: usize| {
+            let p = NamedNode::new("http://example.org/vocab#p").unwrap();
+            let mut dataset = Dataset::new();
+            for i in 0..n {
+                for j in 0..n {
+                    let a = BlankNode::new(format!("a{}", i)).unwrap();
+                    let b = BlankNode::new(format!("b{}", j)).unwrap();
+                    dataset.insert(&Quad::new(
+                        a.clone(),
+                        p.clone(),
+                        b.clone(),
+                        GraphName::DefaultGraph,
+                    ));
+                    dataset.insert(&Quad::new(b, p.clone(), a, GraphName::DefaultGraph));
+                }
+            }
+            dataset
+        };
+        let total_calls = |dataset: &Dataset| {
+            let mut counter = SimpleHndqCallCounter::new(Some(usize::MAX));
+            let mut state = CanonicalizationState::new();
+            canonicalize_with_state::<Sha256, _>(&mut state, dataset, &mut counter).unwrap();
+            counter.sum()
+        };
+
+        for n in 1..=3 {
+            let hashes = first_degree_hashes::<Sha256>(&complete_bipartite(n)).unwrap();
+            assert_eq!(hashes.len(), 2 * n);
+            assert!(hashes.values().all_equal());
+        }
+
+        // the number of calls grows super-exponentially and does not depend on the run
+        let dataset = complete_bipartite(3);
+        assert_eq!(total_calls(&complete_bipartite(1)), 4);
+        assert_eq!(total_calls(&complete_bipartite(2)), 28);
+        assert_eq!(total_calls(&dataset), 258);
+        assert_eq!(total_calls(&dataset), 258);
+
+        // the guard fires exactly when the limit is below the required number of calls
+        let passes = |limit| {
+            canonicalize_core::<Sha256, _>(&dataset, SimpleHndqCallCounter::new(Some(limit)))
+                .is_ok()
+        };
+        assert!(passes(258));
+        assert!(!passes(257));
+
+        // K_{4,4}, i.e., 8 blank nodes in 32 quads, requires 4232 calls
+        // and therefore already exceeds the default limit of 4000
+        assert_eq!(total_calls(&complete_bipartite(4)), 4232);
+        assert!(matches!(
+            canonicalize_core::<Sha256, _>(
+                &complete_bipartite(4),
+                SimpleHndqCallCounter::default()
+            ),
+            Err(CanonicalizationError::HndqCallLimitExceeded { limit: 4000, .. })
+        ));
+    }
+
+    #[test]
+    fn test_issuance_order_beyond_ten_identifiers() {
+        // the temporary identifiers b0, b1, ..., b11 must be taken in issuance order,
+        // not in code point order, which would put b10 and b11 before b2
+        let input: String = (0..12)
+            .map(|i| format!("_:n{} <urn:ex:next> _:n{} .\n", i, (i + 1) % 12))
+            .collect();
+        let dataset: Dataset = oxttl::NQuadsParser::new()
+            .for_slice(input.as_bytes())
+            .map(|q| q.unwrap())
+            .collect();
+
+        let (canonical_issuer, _) = canonicalize_core_with_recording::<Sha256, _>(
+            &dataset,
+            SimpleHndqCallCounter::default(),
+            &|_, _| {},
+            &mut Recording::default(),
+        )
+        .unwrap();
+        let issuance_order: Vec<&str> = canonical_issuer
+            .issued_identifiers_map()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            issuance_order,
+            vec!["n0", "n11", "n10", "n9", "n8", "n7", "n6", "n5", "n4", "n3", "n2", "n1"]
+        );
+        assert!(canonical_issuer
+            .issued_identifiers_map()
+            .values()
+            .enumerate()
+            .all(|(i, v)| *v == format!("c14n{}", i)));
+    }
+
+    #[test]
+    fn test_input_labels_like_canonical_identifiers() {
+        // every input blank node is issued a canonical identifier and relabeled,
+        // so input labels such as c14n0 never survive into the output,
+        // even when they differ from the canonical identifiers they are issued
+        let parse = |input: &str| -> Dataset {
+            oxttl::NQuadsParser::new()
+                .for_slice(input.as_bytes())
+                .map(|q| q.unwrap())
+                .collect()
+        };
+        let colliding = parse(
+            r#"_:c14n0 <urn:ex:p> _:c14n1 .
+_:c14n1 <urn:ex:p> _:c14n2 .
+_:c14n2 <urn:ex:label> "end" .
+"#,
+        );
+        let renamed = parse(
+            r#"_:x <urn:ex:p> _:y .
+_:y <urn:ex:p> _:z .
+_:z <urn:ex:label> "end" .
+"#,
+        );
+
+        let issued_identifiers_map =
+            canonicalize_core::<Sha256, _>(&colliding, SimpleHndqCallCounter::default()).unwrap();
+        assert_eq!(issued_identifiers_map.len(), 3);
+        assert_ne!(issued_identifiers_map["c14n0"], "c14n0");
+        assert_eq!(
+            crate::canonicalize(&colliding).unwrap(),
+            crate::canonicalize(&renamed).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_no_blank_nodes() {
+        let input = r#"<urn:ex:s> <urn:ex:p> "z" <urn:ex:g> .
+<urn:ex:s> <urn:ex:p> "a" .
+<urn:ex:s> <urn:ex:p> <urn:ex:o> .
+"#;
+        let dataset: Dataset = oxttl::NQuadsParser::new()
+            .for_slice(input.as_bytes())
+            .map(|q| q.unwrap())
+            .collect();
+
+        let mut counter = SimpleHndqCallCounter::default();
+        let mut state = CanonicalizationState::new();
+        let issued_identifiers_map =
+            canonicalize_with_state::<Sha256, _>(&mut state, &dataset, &mut counter).unwrap();
+        assert!(issued_identifiers_map.is_empty());
+        assert!(state.hash_to_blank_node_map.is_empty());
+        assert_eq!(counter.sum(), 0);
+
+        // the quads are still sorted into code point order
+        let expected = r#"<urn:ex:s> <urn:ex:p> "a" .
+<urn:ex:s> <urn:ex:p> "z" <urn:ex:g> .
+<urn:ex:s> <urn:ex:p> <urn:ex:o> .
+"#;
+        assert_eq!(crate::canonicalize(&dataset).unwrap(), expected);
+        assert_eq!(serialize(&dataset), expected);
+    }
+
+    #[test]
+    fn test_serialize_default_graph_as_ntriples() {
+        let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+_:e1 <http://example.org/vocab#next> _:e0 .
+_:e1 <http://example.org/vocab#label> "one"@en .
+<urn:ex:s> <urn:ex:p> <urn:ex:o> .
+"#;
+        let dataset: Dataset = oxttl::NQuadsParser::new()
+            .for_slice(input.as_bytes())
+            .map(|q| q.unwrap())
+            .collect();
+        let graph: Graph = oxttl::NTriplesParser::new()
+            .for_slice(input.as_bytes())
+            .map(|t| t.unwrap())
+            .collect();
+
+        let serialized = serialize(&dataset);
+        assert_eq!(serialized, serialize_graph(&graph));
+        assert!(!serialized.contains("  ."));
+        assert_eq!(
+            crate::canonicalize(&dataset).unwrap(),
+            crate::canonicalize_graph(&graph).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_serialize_graph_as_strict_ntriples() {
+        let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+_:e1 <http://example.org/vocab#next> _:e0 .
+_:e1 <http://example.org/vocab#label> "one"@en .
+_:e1 <http://example.org/vocab#value> "1"^^<http://www.w3.org/2001/XMLSchema#integer> .
+<urn:ex:s> <urn:ex:p> "\u0008\u0009\u000a\u000b\u000c\u000d\u0022\u005c\u007f" .
+<urn:ex:s> <urn:ex:p> <urn:ex:o> .
+"#;
+        let graph: Graph = oxttl::NTriplesParser::new()
+            .for_slice(input.as_bytes())
+            .map(|t| t.unwrap())
+            .collect();
+        let canonicalized = crate::canonicalize_graph(&graph).unwrap();
+
+        // oxttl serializes the same triples, in the same order, identically
+        let canonicalized_graph: Graph = oxttl::NTriplesParser::new()
+            .for_slice(canonicalized.as_bytes())
+            .map(|t| t.unwrap())
+            .collect();
+        let mut serializer = oxttl::NTriplesSerializer::new().for_writer(Vec::new());
+        for triple in crate::sort_graph(&canonicalized_graph) {
+            serializer.serialize_triple(&triple).unwrap();
+        }
+        let serialized = String::from_utf8(serializer.finish()).unwrap();
+        assert_eq!(serialize_graph(&canonicalized_graph), serialized);
+        assert_eq!(canonicalized, serialized);
+        assert!(canonicalized.lines().all(|line| line.ends_with(" .")));
+        assert_eq!(canonicalized_graph.len(), graph.len());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_hash_paths() {
+        // manifest inputs whose blank nodes share first degree hashes due to symmetry
+        const SYMMETRIC_INPUTS: [&str; 15] = [
+            "test022", "test024", "test025", "test026", "test027", "test028", "test029", "test064",
+            "test065", "test066", "test067", "test068", "test069", "test070", "test071",
+        ];
+        let parallel = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+
+        for name in SYMMETRIC_INPUTS {
+            let input = std::fs::read(format!("tests/rdfc10/{}-in.nq", name)).unwrap();
+            let expected =
+                std::fs::read_to_string(format!("tests/rdfc10/{}-rdfc10.nq", name)).unwrap();
+            let dataset: Dataset = oxttl::NQuadsParser::new()
+                .for_slice(&input)
+                .map(|q| q.unwrap())
+                .collect();
+            let mut calls = None;
+            for _ in 0..8 {
+                let (issued_identifiers_map, sum) = parallel.install(|| {
+                    let mut counter = SimpleHndqCallCounter::new(None);
+                    let mut state = CanonicalizationState::new();
+                    let issued_identifiers_map =
+                        canonicalize_with_state::<Sha256, _>(&mut state, &dataset, &mut counter)
+                            .unwrap();
+                    (issued_identifiers_map, counter.sum())
+                });
+                let canonicalized =
+                    serialize(&crate::relabel(&dataset, &issued_identifiers_map).unwrap());
+                assert_eq!(canonicalized, expected, "{}", name);
+                // the calls are counted the same regardless of the scheduling
+                assert_eq!(*calls.get_or_insert(sum), sum, "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_first_degree_hash_cache() {
+        let mut inputs: Vec<_> = std::fs::read_dir("tests/rdfc10")
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().ends_with("-in.nq"))
+            .collect();
+        inputs.sort();
+        assert!(!inputs.is_empty());
+
+        for path in inputs {
+            let input = std::fs::read(&path).unwrap();
+            let dataset: Dataset = oxttl::NQuadsParser::new()
+                .for_slice(&input)
+                .map(|q| q.unwrap())
+                .collect();
+
+            // the state after step 3 of the canonicalization algorithm, with and without
+            // the first degree hashes cached
+            let mut cached = CanonicalizationState::new();
+            cached.update_blank_node_to_quads_map(&dataset);
+            cached.first_degree_hash_map = first_degree_hashes::<Sha256>(&dataset)
+                .unwrap()
+                .into_iter()
+                .collect();
+            let mut uncached = CanonicalizationState::new();
+            uncached.update_blank_node_to_quads_map(&dataset);
+
+            let issuer = IdentifierIssuer::new("b");
+            for quad in &cached.quads {
+                let related = [
+                    match &quad.subject {
+                        Subject::BlankNode(n) => Some((n, HashRelatedBlankNodePosition::Subject)),
+                        _ => None,
+                    },
+                    match &quad.object {
+                        Term::BlankNode(n) => Some((n, HashRelatedBlankNodePosition::Object)),
+                        _ => None,
+                    },
+                    match &quad.graph_name {
+                        GraphName::BlankNode(n) => Some((n, HashRelatedBlankNodePosition::Graph)),
+                        _ => None,
+                    },
+                ];
+                for (n, position) in related.into_iter().flatten() {
+                    let related = n.as_str().to_string();
+                    assert_eq!(
+                        hash_related_blank_node::<Sha256>(
+                            &cached, &related, quad, &issuer, position
+                        )
+                        .unwrap(),
+                        hash_related_blank_node::<Sha256>(
+                            &uncached, &related, quad, &issuer, position
+                        )
+                        .unwrap(),
+                        "{}",
+                        path.display()
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_first_degree_hashes() {
+        let parallel = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+
+        let mut inputs: Vec<_> = std::fs::read_dir("tests/rdfc10")
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().ends_with("-in.nq"))
+            .collect();
+        inputs.sort();
+        assert!(!inputs.is_empty());
+
+        for path in inputs {
+            let input = std::fs::read(&path).unwrap();
+            let dataset: Dataset = oxttl::NQuadsParser::new()
+                .for_slice(&input)
+                .map(|q| q.unwrap())
+                .collect();
+            // the manifest canonicalizes test075 with SHA-384 and the other inputs with SHA-256
+            let canonicalized = parallel.install(|| {
+                if path.ends_with("test075-in.nq") {
+                    crate::canonicalize_with::<sha2::Sha384>(&dataset, &Default::default())
+                } else {
+                    crate::canonicalize(&dataset)
+                }
+            });
+            let expected_path = path.to_string_lossy().replace("-in.nq", "-rdfc10.nq");
+            match std::fs::read_to_string(expected_path) {
+                Ok(expected) => assert_eq!(canonicalized.unwrap(), expected, "{}", path.display()),
+                // a negative test, e.g., the poison graph exceeding the call limit
+                Err(_) => assert!(canonicalized.is_err(), "{}", path.display()),
+            }
+
+            // the hashes computed in parallel are exactly the sequentially computed ones
+            let mut state = CanonicalizationState::new();
+            let mut counter = SimpleHndqCallCounter::default();
+            let _ = parallel.install(|| {
+                canonicalize_with_state::<Sha256, _>(&mut state, &dataset, &mut counter)
+            });
+            let first_degree_hash_map: BTreeMap<_, _> =
+                state.first_degree_hash_map.into_iter().collect();
+            assert_eq!(
+                first_degree_hash_map,
+                first_degree_hashes::<Sha256>(&dataset).unwrap(),
+                "{}",
+                path.display()
+            );
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_wiped_on_error_and_panic() {
+        use std::sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        };
+        use zeroize::Zeroize;
+
+        struct Flag(Arc<AtomicBool>);
+        impl Zeroize for Flag {
+            fn zeroize(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        // an early return with an error, here when the call limit is exceeded
+        fn add_call(data_to_hash: Flag) -> Result<(), CanonicalizationError> {
+            let _data_to_hash = wiped(data_to_hash);
+            SimpleHndqCallCounter::new(Some(0)).add("e0")?;
+            unreachable!()
+        }
+        let wiped_on_error = Arc::new(AtomicBool::new(false));
+        let result = add_call(Flag(Arc::clone(&wiped_on_error)));
+        assert!(matches!(
+            result,
+            Err(CanonicalizationError::HndqCallLimitExceeded { limit: 0, .. })
+        ));
+        assert!(wiped_on_error.load(Ordering::SeqCst));
+
+        // unwinding from a panic
+        let wiped_on_panic = Arc::new(AtomicBool::new(false));
+        let result = std::panic::catch_unwind(|| {
+            let _data_to_hash = wiped(Flag(Arc::clone(&wiped_on_panic)));
+            panic!("interrupted");
+        });
+        assert!(result.is_err());
+        assert!(wiped_on_panic.load(Ordering::SeqCst));
+
+        // the canonicalization fails as usual when the limit is exceeded
+        // within the Hash N-Degree Quads algorithm
+        let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+_:e1 <http://example.org/vocab#next> _:e2 .
+_:e2 <http://example.org/vocab#next> _:e0 .
+"#;
+        let dataset: Dataset = oxttl::NQuadsParser::new()
+            .for_reader(input.as_bytes())
+            .map(|q| q.unwrap())
+            .collect();
+        assert!(matches!(
+            canonicalize_core::<Sha256, _>(&dataset, SimpleHndqCallCounter::new(Some(1))),
+            Err(CanonicalizationError::HndqCallLimitExceeded { limit: 1, .. })
+        ));
+    }
+
+    #[cfg(feature = "rdf-star")]
+    #[test]
+    fn test_blank_nodes_in_quoted_triples() {
+        // _:x <urn:ex:says> << _:y <urn:ex:knows> _:z >> .
+        // _:y <urn:ex:name> "y" .
+        // _:z <urn:ex:name> "z" .
+        fn dataset(x: &str, y: &str, z: &str) -> Dataset {
+            let p = |name: &str| NamedNode::new(format!("urn:ex:{}", name)).unwrap();
+            let quoted = Triple::new(
+                BlankNode::new(y).unwrap(),
+                p("knows"),
+                BlankNode::new(z).unwrap(),
+            );
+            let mut dataset = Dataset::new();
+            dataset.insert(QuadRef::new(
+                &BlankNode::new(x).unwrap(),
+                &p("says"),
+                &Term::Triple(Box::new(quoted)),
+                GraphNameRef::DefaultGraph,
+            ));
+            for (bnode, name) in [(y, "y"), (z, "z")] {
+                dataset.insert(QuadRef::new(
+                    &BlankNode::new(bnode).unwrap(),
+                    &p("name"),
+                    &Term::from(oxrdf::Literal::new_simple_literal(name)),
+                    GraphNameRef::DefaultGraph,
+                ));
+            }
+            dataset
+        }
+
+        let dataset1 = dataset("x", "y", "z");
+        let dataset2 = dataset("b2", "b0", "b1");
+
+        let mut state = CanonicalizationState::new();
+        let issued_identifiers_map = canonicalize_with_state::<Sha256, _>(
+            &mut state,
+            &dataset1,
+            &mut SimpleHndqCallCounter::default(),
+        )
+        .unwrap();
+        // blank nodes that only appear in quoted triples are labeled as well
+        assert_eq!(issued_identifiers_map.len(), 3);
+        assert_eq!(state.blank_node_to_quads_map["y"].len(), 2);
+
+        let canonicalized1 = crate::canonicalize(&dataset1).unwrap();
+        let canonicalized2 = crate::canonicalize(&dataset2).unwrap();
+        assert_eq!(canonicalized1, canonicalized2);
+        assert_eq!(canonicalized1.matches("_:c14n").count(), 5);
+        assert!(!canonicalized1.contains("_:x") && !canonicalized1.contains("_:b0"));
+    }
 }
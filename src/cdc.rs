@@ -0,0 +1,121 @@
+use crate::{api::canonicalize, CanonicalizationError};
+use oxrdf::Dataset;
+use sha2::{Digest, Sha256};
+use std::ops::Range;
+
+/// Pseudo-random values for the Gear rolling hash, one per byte value,
+/// generated with SplitMix64 so that chunk boundaries are reproducible
+/// across platforms and versions.
+const GEAR_TABLE: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+/// A byte range over the serialized canonical form and the SHA-256 digest
+/// of the bytes in that range.
+pub type CdcChunk = (Range<usize>, Vec<u8>);
+
+/// Returns the content-defined chunks of the serialized canonical form of
+/// the input dataset, as pairs of a byte range over the canonical N-Quads
+/// document and the SHA-256 digest of that chunk.
+///
+/// Chunk boundaries are determined by a Gear rolling hash over the canonical
+/// bytes, so a local change to the dataset only affects the chunks around it
+/// and unchanged regions can be shared between peers.
+/// Chunks are on average about `avg_chunk_size` bytes long (rounded up to a
+/// power of two, and clamped to the largest power of two that fits in `usize`),
+/// and are at least a quarter and at most four times as long, except for the
+/// last one.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::{canonical_cdc_chunks, canonicalize};
+/// use sha2::{Digest, Sha256};
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
+/// _:e0 <http://example.org/vocab#prev> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#next> _:e2 _:g .
+/// _:e1 <http://example.org/vocab#prev> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#next> _:e0 _:g .
+/// _:e2 <http://example.org/vocab#prev> _:e1 _:g .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let canonicalized = canonicalize(&input_dataset).unwrap();
+/// let chunks = canonical_cdc_chunks(&input_dataset, 64).unwrap();
+///
+/// let mut offset = 0;
+/// for (range, chunk_hash) in chunks {
+///     assert_eq!(range.start, offset);
+///     assert_eq!(Sha256::digest(&canonicalized.as_bytes()[range.clone()]).to_vec(), chunk_hash);
+///     offset = range.end;
+/// }
+/// assert_eq!(offset, canonicalized.len());
+///
+/// // an average larger than the document yields a single chunk
+/// let chunks = canonical_cdc_chunks(&input_dataset, usize::MAX).unwrap();
+/// assert_eq!(chunks.len(), 1);
+/// assert_eq!(chunks[0].0, 0..canonicalized.len());
+/// ```
+pub fn canonical_cdc_chunks(
+    input_dataset: &Dataset,
+    avg_chunk_size: usize,
+) -> Result<Vec<CdcChunk>, CanonicalizationError> {
+    let canonicalized = canonicalize(input_dataset)?;
+    let bytes = canonicalized.as_bytes();
+
+    let avg_chunk_size = avg_chunk_size
+        .max(1)
+        .checked_next_power_of_two()
+        .unwrap_or(1 << (usize::BITS - 1));
+    let min_chunk_size = (avg_chunk_size / 4).max(1);
+    let max_chunk_size = avg_chunk_size.saturating_mul(4);
+    // use the most significant bits, which depend on the last 64 bytes
+    let mask_bits = avg_chunk_size.trailing_zeros();
+    let mask = match mask_bits {
+        0 => 0,
+        bits => u64::MAX << (64 - bits),
+    };
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut rolling_hash: u64 = 0;
+    for (i, byte) in bytes.iter().enumerate() {
+        rolling_hash = (rolling_hash << 1).wrapping_add(GEAR_TABLE[*byte as usize]);
+        let len = i + 1 - start;
+        if (len >= min_chunk_size && rolling_hash & mask == 0) || len >= max_chunk_size {
+            chunks.push(chunk(bytes, start..i + 1));
+            start = i + 1;
+            rolling_hash = 0;
+        }
+    }
+    if start < bytes.len() {
+        chunks.push(chunk(bytes, start..bytes.len()));
+    }
+
+    Ok(chunks)
+}
+
+fn chunk(bytes: &[u8], range: Range<usize>) -> CdcChunk {
+    let chunk_hash = Sha256::digest(&bytes[range.clone()]).to_vec();
+    (range, chunk_hash)
+}
@@ -0,0 +1,116 @@
+use crate::api::{blank_node_identifiers, blank_node_identifiers_in_quad};
+use oxrdf::Dataset;
+use std::collections::{BTreeMap, HashMap};
+
+/// Partitions the input dataset into its blank node connected components,
+/// where two blank nodes are connected if they appear in the same quad.
+///
+/// Blank nodes in different components never appear in the same quad,
+/// so each component has a canonical form of its own, and can be canonicalized
+/// independently of the others, e.g., in parallel or only when it changes.
+/// The ground quads, which have no blank nodes, are returned together as the first dataset
+/// if there are any, followed by the components ordered by their smallest blank node
+/// identifier.
+///
+/// Note that the canonical forms of the components cannot simply be combined into the
+/// canonical form of the whole dataset: every canonicalized component is numbered
+/// `c14n0`, `c14n1`, ... on its own, and since the Hash N-Degree Quads algorithm hashes
+/// the canonical identifiers issued so far, the labeling within a component can even
+/// depend on how many identifiers the other components take up.
+/// A combined output still has to be produced by canonicalizing the whole dataset.
+///
+/// # Examples
+///
+/// ```
+/// use oxrdf::Dataset;
+/// use oxttl::NQuadsParser;
+/// use rdf_canon::connected_components;
+/// use std::io::Cursor;
+///
+/// let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+/// _:e1 <http://example.org/vocab#next> _:e0 .
+/// _:f0 <http://example.org/vocab#next> _:f0 _:g .
+/// <urn:ex:s> <urn:ex:p> <urn:ex:o> .
+/// "#;
+///
+/// let input_quads = NQuadsParser::new()
+///     .for_reader(Cursor::new(input))
+///     .map(|x| x.unwrap());
+/// let input_dataset = Dataset::from_iter(input_quads);
+/// let components = connected_components(&input_dataset);
+///
+/// assert_eq!(components.len(), 3);
+/// assert_eq!(components[0].len(), 1); // the ground quad
+/// assert_eq!(components[1].len(), 2); // _:e0 and _:e1
+/// assert_eq!(components[2].len(), 1); // _:f0 and _:g
+/// ```
+pub fn connected_components(input_dataset: &Dataset) -> Vec<Dataset> {
+    // the identifiers are sorted, so the root of each set, which is the smallest index
+    // in it, is also the smallest identifier in the component
+    let identifiers = blank_node_identifiers(input_dataset);
+    let index: HashMap<&str, usize> = identifiers
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (*id, i))
+        .collect();
+    let mut sets = DisjointSets::new(identifiers.len());
+    for quad in input_dataset.iter() {
+        let mut blank_nodes = blank_node_identifiers_in_quad(quad).into_iter();
+        if let Some(first) = blank_nodes.next() {
+            for other in blank_nodes {
+                sets.union(index[first], index[other]);
+            }
+        }
+    }
+
+    let mut ground = Dataset::new();
+    let mut components: BTreeMap<usize, Dataset> = BTreeMap::new();
+    for quad in input_dataset.iter() {
+        match blank_node_identifiers_in_quad(quad).first() {
+            Some(id) => {
+                components
+                    .entry(sets.find(index[id]))
+                    .or_default()
+                    .insert(quad);
+            }
+            None => {
+                ground.insert(quad);
+            }
+        }
+    }
+
+    (!ground.is_empty())
+        .then_some(ground)
+        .into_iter()
+        .chain(components.into_values())
+        .collect()
+}
+
+/// A union-find forest over the indices of blank nodes,
+/// where the root of each set is its smallest index
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(size: usize) -> Self {
+        Self {
+            parents: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[a.max(b)] = a.min(b);
+        }
+    }
+}
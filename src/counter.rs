@@ -1,17 +1,44 @@
 use crate::CanonicalizationError;
 use std::{collections::HashMap, fmt};
 
+/// The default limit, which a complete bipartite graph of 4 + 4 blank nodes
+/// sharing one first degree hash (32 quads) already exceeds with 4232 calls
 const DEFAULT_HNDQ_CALL_LIMIT: usize = 4000;
 
 pub trait HndqCallCounter {
     fn new(max_calls: Option<usize>) -> Self;
     fn add(&mut self, identifier: &str) -> Result<(), CanonicalizationError>;
     fn sum(&self) -> usize;
+    /// Records the recursion depth of a call to the Hash N-Degree Quads algorithm,
+    /// which is 1 for the calls made directly by the canonicalization algorithm.
+    /// Counters that do not track the depth can ignore it.
+    fn record_depth(&mut self, _depth: usize) {}
+    /// Returns the maximum recursion depth recorded, or 0 if not tracked.
+    fn max_depth(&self) -> usize {
+        0
+    }
 }
 
+/// `Send` if the `parallel` feature is enabled, since the counter is then shared among
+/// the threads running the Hash N-Degree Quads algorithm, and implemented by every type otherwise
+#[cfg(feature = "parallel")]
+pub trait MaybeSend: Send {}
+
+#[cfg(feature = "parallel")]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// `Send` if the `parallel` feature is enabled, since the counter is then shared among
+/// the threads running the Hash N-Degree Quads algorithm, and implemented by every type otherwise
+#[cfg(not(feature = "parallel"))]
+pub trait MaybeSend {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T: ?Sized> MaybeSend for T {}
+
 pub struct SimpleHndqCallCounter {
     counter: usize,
     limit: usize,
+    max_depth: usize,
 }
 
 impl Default for SimpleHndqCallCounter {
@@ -19,6 +46,7 @@ impl Default for SimpleHndqCallCounter {
         Self {
             counter: Default::default(),
             limit: DEFAULT_HNDQ_CALL_LIMIT,
+            max_depth: 0,
         }
     }
 }
@@ -29,13 +57,20 @@ impl HndqCallCounter for SimpleHndqCallCounter {
             Some(limit) => limit,
             None => DEFAULT_HNDQ_CALL_LIMIT,
         };
-        Self { counter: 0, limit }
+        Self {
+            counter: 0,
+            limit,
+            max_depth: 0,
+        }
     }
 
     fn add(&mut self, _identifier: &str) -> Result<(), CanonicalizationError> {
         self.counter += 1;
         if self.counter > self.limit {
-            Err(CanonicalizationError::HndqCallLimitExceeded(self.limit))
+            Err(CanonicalizationError::HndqCallLimitExceeded {
+                limit: self.limit,
+                partial_map: HashMap::new(),
+            })
         } else {
             Ok(())
         }
@@ -44,6 +79,14 @@ impl HndqCallCounter for SimpleHndqCallCounter {
     fn sum(&self) -> usize {
         self.counter
     }
+
+    fn record_depth(&mut self, depth: usize) {
+        self.max_depth = self.max_depth.max(depth);
+    }
+
+    fn max_depth(&self) -> usize {
+        self.max_depth
+    }
 }
 
 impl fmt::Debug for SimpleHndqCallCounter {
@@ -51,6 +94,7 @@ impl fmt::Debug for SimpleHndqCallCounter {
         f.debug_struct("")
             .field("counter", &self.counter)
             .field("limit", &self.limit)
+            .field("max_depth", &self.max_depth)
             .finish()
     }
 }
@@ -58,6 +102,7 @@ impl fmt::Debug for SimpleHndqCallCounter {
 pub struct PerNodeHndqCallCounter {
     counter: HashMap<String, usize>,
     limit: usize,
+    max_depth: usize,
 }
 
 impl Default for PerNodeHndqCallCounter {
@@ -65,6 +110,7 @@ impl Default for PerNodeHndqCallCounter {
         Self {
             counter: Default::default(),
             limit: DEFAULT_HNDQ_CALL_LIMIT,
+            max_depth: 0,
         }
     }
 }
@@ -78,6 +124,7 @@ impl HndqCallCounter for PerNodeHndqCallCounter {
         Self {
             counter: Default::default(),
             limit,
+            max_depth: 0,
         }
     }
 
@@ -88,7 +135,10 @@ impl HndqCallCounter for PerNodeHndqCallCounter {
             .and_modify(|c| *c += 1)
             .or_insert(1);
         if current > &mut self.limit {
-            Err(CanonicalizationError::HndqCallLimitExceeded(self.limit))
+            Err(CanonicalizationError::HndqCallLimitExceeded {
+                limit: self.limit,
+                partial_map: HashMap::new(),
+            })
         } else {
             Ok(())
         }
@@ -101,6 +151,14 @@ impl HndqCallCounter for PerNodeHndqCallCounter {
             .reduce(|acc, v| acc + v)
             .unwrap_or(0)
     }
+
+    fn record_depth(&mut self, depth: usize) {
+        self.max_depth = self.max_depth.max(depth);
+    }
+
+    fn max_depth(&self) -> usize {
+        self.max_depth
+    }
 }
 
 impl fmt::Debug for PerNodeHndqCallCounter {
@@ -109,6 +167,7 @@ impl fmt::Debug for PerNodeHndqCallCounter {
             .field("counter", &self.counter)
             .field("limit", &self.limit)
             .field("sum", &self.sum())
+            .field("max_depth", &self.max_depth)
             .finish()
     }
 }
@@ -1,22 +1,71 @@
-use oxrdf::BlankNodeIdParseError;
+use oxttl::{TurtleParseError, TurtleSyntaxError};
+use std::{collections::HashMap, sync::Arc};
 use thiserror::Error;
 
 #[derive(Error, Debug, Clone)]
 pub enum CanonicalizationError {
-    #[error("Base16 encoding failed.")]
-    Base16EncodingFailed(base16ct::Error),
+    #[error("Base16 encoding failed: {0}")]
+    Base16EncodingFailed(#[from] base16ct::Error),
     #[error("Reference blank node identifier does not exist in the canonicalization state.")]
     QuadsNotExist,
     #[error("Canonical identifier does not exist for the given blank node.")]
     CanonicalIdentifierNotExist,
-    #[error("Parsing blank node identifier failed.")]
-    BlankNodeIdParseError,
-    #[error("The number of calls to the Hash N-degree Quads algorithm have exceeded the limit of {0}.")]
-    HndqCallLimitExceeded(usize),
+    /// Holds the string that is not a valid blank node identifier,
+    /// e.g., a label taken from an issued identifiers map.
+    #[error("Parsing blank node identifier {0} failed.")]
+    BlankNodeIdParseError(String),
+    /// `partial_map` holds the canonical identifiers issued before the limit was exceeded,
+    /// which may help diagnose the input even though the canonicalization is incomplete.
+    #[error("The number of calls to the Hash N-degree Quads algorithm have exceeded the limit of {limit}.")]
+    HndqCallLimitExceeded {
+        limit: usize,
+        partial_map: HashMap<String, String>,
+    },
+    /// `count` is the number of quads in which the blank node `node` appears,
+    /// which exceeds `CanonicalizationOptions::max_quads_per_blank_node`.
+    #[error("Blank node {node} appears in {count} quads, exceeding the limit.")]
+    BlankNodeTooConnected { node: String, count: usize },
+    #[error("Input blank node identifier {0} collides with the canonical identifier prefix.")]
+    PrefixCollision(String),
+    #[error("The canonical label {0} is not a valid and unique blank node identifier.")]
+    InvalidCanonicalLabel(String),
+    #[error("Unsupported hash algorithm: {0}")]
+    UnsupportedHashAlgorithm(String),
+    #[error("Unsupported media type: {0}")]
+    UnsupportedMediaType(String),
+    #[error("Reading the input failed: {0}")]
+    Io(Arc<std::io::Error>),
+    /// `line` and `column` are 1-based and point at the start of the invalid input.
+    #[error("Parsing the input failed at line {line}, column {column}: {}", .source.message())]
+    Parse {
+        line: u64,
+        column: u64,
+        source: Arc<TurtleSyntaxError>,
+    },
 }
 
-impl From<BlankNodeIdParseError> for CanonicalizationError {
-    fn from(_: BlankNodeIdParseError) -> Self {
-        Self::BlankNodeIdParseError
+impl From<std::io::Error> for CanonicalizationError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(Arc::new(e))
+    }
+}
+
+impl From<TurtleSyntaxError> for CanonicalizationError {
+    fn from(e: TurtleSyntaxError) -> Self {
+        let start = e.location().start;
+        Self::Parse {
+            line: start.line + 1,
+            column: start.column + 1,
+            source: Arc::new(e),
+        }
+    }
+}
+
+impl From<TurtleParseError> for CanonicalizationError {
+    fn from(e: TurtleParseError) -> Self {
+        match e {
+            TurtleParseError::Syntax(e) => e.into(),
+            TurtleParseError::Io(e) => e.into(),
+        }
     }
 }
@@ -0,0 +1,83 @@
+use crate::api::{canonicalize, nquads_parser};
+use oxrdf::Dataset;
+use std::{
+    ffi::{c_char, c_int, CStr, CString},
+    panic, ptr,
+};
+
+/// The canonical form was written to the output.
+pub const RDF_CANON_OK: c_int = 0;
+/// A pointer argument was null.
+pub const RDF_CANON_ERROR_NULL_POINTER: c_int = 1;
+/// The input is not valid UTF-8.
+pub const RDF_CANON_ERROR_INVALID_UTF8: c_int = 2;
+/// The input is not valid N-Quads.
+pub const RDF_CANON_ERROR_PARSE: c_int = 3;
+/// The canonicalization failed, e.g., the call limit was exceeded.
+pub const RDF_CANON_ERROR_CANONICALIZATION: c_int = 4;
+/// A panic occurred in the library, which was caught instead of unwinding into the caller.
+pub const RDF_CANON_ERROR_PANIC: c_int = 5;
+
+/// Parses the null-terminated N-Quads document `nquads` and stores its serialized
+/// canonical form, null-terminated, in `*out` and its length in bytes, excluding the
+/// terminating null, in `*out_len`.
+/// Returns `RDF_CANON_OK` on success, or one of the `RDF_CANON_ERROR_*` codes otherwise,
+/// in which case `*out` is set to null and `*out_len` to 0 if the pointers are not null.
+/// The output must be released with `rdf_canon_free`.
+///
+/// # Safety
+///
+/// `nquads` must be null or point to a null-terminated string, and `out` and `out_len`
+/// must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn rdf_canon_canonicalize(
+    nquads: *const c_char,
+    out: *mut *mut c_char,
+    out_len: *mut usize,
+) -> c_int {
+    if out.is_null() || out_len.is_null() {
+        return RDF_CANON_ERROR_NULL_POINTER;
+    }
+    *out = ptr::null_mut();
+    *out_len = 0;
+    if nquads.is_null() {
+        return RDF_CANON_ERROR_NULL_POINTER;
+    }
+
+    // (unwinding across the FFI boundary is undefined behavior)
+    let canonicalized = match panic::catch_unwind(|| canonicalize_nquads(CStr::from_ptr(nquads))) {
+        Ok(Ok(canonicalized)) => canonicalized,
+        Ok(Err(code)) => return code,
+        Err(_) => return RDF_CANON_ERROR_PANIC,
+    };
+
+    *out_len = canonicalized.as_bytes().len();
+    *out = canonicalized.into_raw();
+    RDF_CANON_OK
+}
+
+fn canonicalize_nquads(nquads: &CStr) -> Result<CString, c_int> {
+    let input = nquads.to_str().map_err(|_| RDF_CANON_ERROR_INVALID_UTF8)?;
+    let input_dataset = nquads_parser()
+        .for_slice(input.as_bytes())
+        .collect::<Result<Dataset, _>>()
+        .map_err(|_| RDF_CANON_ERROR_PARSE)?;
+    // (the canonical form never contains a null character, which is escaped in N-Quads)
+    match canonicalize(&input_dataset).map(CString::new) {
+        Ok(Ok(canonicalized)) => Ok(canonicalized),
+        _ => Err(RDF_CANON_ERROR_CANONICALIZATION),
+    }
+}
+
+/// Releases an output of `rdf_canon_canonicalize`. Does nothing if `s` is null.
+///
+/// # Safety
+///
+/// `s` must be null or a pointer stored by `rdf_canon_canonicalize` that has not been
+/// released yet.
+#[no_mangle]
+pub unsafe extern "C" fn rdf_canon_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
@@ -1,19 +1,56 @@
 pub mod api;
+pub mod automorphism;
 pub mod canon;
+pub mod cdc;
+pub mod components;
 pub mod counter;
 pub mod error;
+#[cfg(feature = "cffi")]
+pub mod ffi;
 #[cfg(feature = "log")]
 pub mod logger;
+#[cfg(feature = "transcript")]
+pub mod transcript;
+#[cfg(feature = "uuid")]
+pub use crate::api::canonical_uuid;
+#[cfg(feature = "unicode-normalization")]
+pub use crate::api::NormForm;
 pub use crate::api::{
-    canonicalize, canonicalize_graph, canonicalize_graph_with, canonicalize_quads,
-    canonicalize_quads_with, canonicalize_with, issue, issue_graph, issue_graph_with, issue_quads,
-    issue_quads_with, issue_with, relabel, relabel_graph, relabel_quads, sort, sort_graph,
-    CanonicalizationOptions,
+    canonicalization_signing_base, canonicalize, canonicalize_cow, canonicalize_documents,
+    canonicalize_explorable, canonicalize_from_reader, canonicalize_full, canonicalize_graph,
+    canonicalize_graph_with, canonicalize_graphs, canonicalize_media_type, canonicalize_quads,
+    canonicalize_quads_checked, canonicalize_quads_with, canonicalize_sorted_by,
+    canonicalize_split, canonicalize_stream, canonicalize_to_bytes, canonicalize_to_canon_quads,
+    canonicalize_to_dataset, canonicalize_to_sorted_quads, canonicalize_to_writer,
+    canonicalize_triples, canonicalize_triples_with, canonicalize_with,
+    canonicalize_with_algorithm, compare_hash_algorithms, dataset_hash, estimate_complexity,
+    explain_labeling, find_dangling_mappings, hash_distribution_stats, is_isomorphic,
+    is_isomorphic_graph, is_isomorphic_graph_with, is_isomorphic_with, is_normalized_dataset,
+    issue, issue_graph, issue_graph_with, issue_graph_with_stats, issue_lookup, issue_ordered,
+    issue_ordered_with, issue_quads, issue_quads_with, issue_quads_with_stats, issue_with,
+    issue_with_algorithm, issue_with_arbitrary_names, issue_with_chosen_paths, issue_with_counter,
+    issue_with_issuer, issue_with_stats, recanonicalize, relabel, relabel_graph, relabel_quads,
+    relabel_quads_lenient, relabel_triples, relabel_with_fallback, relabel_with_fn, sort,
+    sort_graph, sort_triples, term_inventory, verify_canonical, verify_canonical_stream,
+    verify_relabeling, CanonQuad, CanonicalizationOptions, CanonicalizationOptionsBuilder,
+    CanonicalizationResult, ComplexityEstimate, CounterStrategy, ExplorableCanonicalization,
+    HashAlgorithm, HashAlgorithmComparison, HashDistributionStats, IssuedIdentifiersMap,
+    LabelExplanation, LabelingStep, PrefixCollisionPolicy, RelabelFallback, TermInventory,
 };
-pub use crate::canon::serialize;
+pub use crate::automorphism::automorphism_count;
+pub use crate::canon::{
+    serialize, serialize_to_writer, CanonicalizationStats, ChosenPathTrace, IdentifierIssuer,
+};
+pub use crate::cdc::canonical_cdc_chunks;
+pub use crate::components::connected_components;
 pub use crate::error::CanonicalizationError;
 #[cfg(feature = "log")]
 pub use crate::logger::YamlLayer;
+#[cfg(feature = "transcript")]
+pub use crate::{
+    api::issue_with_transcript,
+    transcript::{TranscriptEntry, TranscriptOperation},
+};
 
 #[cfg(test)]
 mod tests {
@@ -89,6 +126,7 @@ mod tests {
                 input_dataset,
                 &CanonicalizationOptions {
                     hndq_call_limit: None,
+                    ..Default::default()
                 },
             )
         };
@@ -97,6 +135,7 @@ mod tests {
                 input_dataset,
                 &CanonicalizationOptions {
                     hndq_call_limit: None,
+                    ..Default::default()
                 },
             )
         };
@@ -155,7 +194,7 @@ mod tests {
                     )
                 }
                 "rdfc:RDFC10NegativeEvalTest" => match canonicalize(&input_dataset) {
-                    Err(CanonicalizationError::HndqCallLimitExceeded(_)) => {}
+                    Err(CanonicalizationError::HndqCallLimitExceeded { .. }) => {}
                     _ => panic!("FAILED: {} - {}", test_id, test_name),
                 },
                 _ => panic!("test type {} is not supported", test_type),
@@ -168,6 +207,63 @@ mod tests {
         }
     }
 
+    #[test]
+    fn canonicalize_with_runtime_hash_algorithm() {
+        use crate::{canonicalize_with_algorithm, issue_with_algorithm, HashAlgorithm};
+
+        let manifest_file = File::open("tests/manifest.jsonld").unwrap();
+        let manifest: TestManifest =
+            serde_json::from_reader(BufReader::new(manifest_file)).unwrap();
+        let options = CanonicalizationOptions::default();
+
+        // the runtime dispatch must agree with the expected results of the test suite,
+        // including the entries using SHA-384
+        for entry in manifest.entries {
+            let input_file = File::open(format!("tests/{}", entry.action)).unwrap();
+            let input_dataset: Dataset = NQuadsParser::new()
+                .for_reader(BufReader::new(input_file))
+                .map(|x| x.unwrap())
+                .collect();
+            let hash_algorithm: HashAlgorithm = entry
+                .hash_algorithm
+                .as_deref()
+                .map_or(Ok(HashAlgorithm::default()), str::parse)
+                .unwrap();
+
+            match entry.r#type.as_str() {
+                "rdfc:RDFC10EvalTest" => {
+                    let expected_output =
+                        std::fs::read_to_string(format!("tests/{}", entry.result.unwrap()))
+                            .unwrap();
+                    assert_eq!(
+                        canonicalize_with_algorithm(&input_dataset, hash_algorithm, &options)
+                            .unwrap(),
+                        expected_output,
+                        "{}",
+                        entry.id
+                    );
+                }
+                "rdfc:RDFC10MapTest" => {
+                    let output_file =
+                        File::open(format!("tests/{}", entry.result.unwrap())).unwrap();
+                    let expected_output: HashMap<String, String> =
+                        serde_json::from_reader(BufReader::new(output_file)).unwrap();
+                    assert_eq!(
+                        issue_with_algorithm(&input_dataset, hash_algorithm, &options).unwrap(),
+                        expected_output,
+                        "{}",
+                        entry.id
+                    );
+                }
+                _ => assert!(
+                    canonicalize_with_algorithm(&input_dataset, hash_algorithm, &options).is_err(),
+                    "{}",
+                    entry.id
+                ),
+            }
+        }
+    }
+
     #[test]
     fn use_sha384() {
         use crate::{canonicalize_with, CanonicalizationOptions};
@@ -203,6 +299,1139 @@ _:c14n3 <http://example.org/vocab#prev> _:c14n1 _:c14n0 .
         assert_eq!(canonicalized, expected);
     }
 
+    #[test]
+    fn parse_hash_algorithm() {
+        use crate::{CanonicalizationError, HashAlgorithm};
+
+        assert_eq!("SHA256".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Sha256);
+        assert_eq!("sha-384".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Sha384);
+        assert_eq!("SHA512".parse::<HashAlgorithm>().unwrap(), HashAlgorithm::Sha512);
+        assert_eq!(HashAlgorithm::Sha384.to_string(), "SHA384");
+        assert!(matches!(
+            "MD5".parse::<HashAlgorithm>(),
+            Err(CanonicalizationError::UnsupportedHashAlgorithm(name)) if name == "MD5"
+        ));
+    }
+
+    #[test]
+    fn per_node_hndq_call_counter() {
+        use crate::{
+            counter::{HndqCallCounter, PerNodeHndqCallCounter, SimpleHndqCallCounter},
+            issue_with, issue_with_counter, CanonicalizationError, CanonicalizationOptions,
+            CounterStrategy,
+        };
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+
+        // four disjoint 3-cycles: each blank node needs few calls,
+        // but the calls add up over all the blank nodes
+        let mut input = String::new();
+        for cycle in 0..4 {
+            for i in 0..3 {
+                input += &format!(
+                    "_:c{cycle}n{i} <http://example.org/vocab#next> _:c{cycle}n{} .\n",
+                    (i + 1) % 3
+                );
+            }
+        }
+        let input_quads = NQuadsParser::new()
+            .for_slice(input.as_bytes())
+            .map(|x| x.unwrap());
+        let input_dataset = Dataset::from_iter(input_quads);
+
+        let issued_identifiers_map = issue_with_counter::<Sha256, _>(
+            &input_dataset,
+            PerNodeHndqCallCounter::new(Some(3)),
+        )
+        .unwrap();
+        let expected = issue_with::<Sha256>(&input_dataset, &CanonicalizationOptions::default())
+            .unwrap();
+        assert_eq!(issued_identifiers_map, expected);
+
+        assert!(matches!(
+            issue_with_counter::<Sha256, _>(&input_dataset, SimpleHndqCallCounter::new(Some(3))),
+            Err(CanonicalizationError::HndqCallLimitExceeded { limit: 3, .. })
+        ));
+
+        // the same counters selected through the options
+        let issue = |counter_strategy| {
+            let options = CanonicalizationOptions {
+                hndq_call_limit: Some(3),
+                counter_strategy,
+                ..Default::default()
+            };
+            issue_with::<Sha256>(&input_dataset, &options)
+        };
+        assert_eq!(issue(CounterStrategy::PerNode).unwrap(), expected);
+        assert!(matches!(
+            issue(CounterStrategy::Global),
+            Err(CanonicalizationError::HndqCallLimitExceeded { limit: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn isomorphic_datasets() {
+        use crate::is_isomorphic;
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+
+        let parse = |input: &str| {
+            Dataset::from_iter(
+                NQuadsParser::new()
+                    .for_slice(input.as_bytes())
+                    .map(|x| x.unwrap()),
+            )
+        };
+        let cycle = parse(
+            "_:a <urn:ex:p> _:b _:g .\n_:b <urn:ex:p> _:c _:g .\n_:c <urn:ex:p> _:a _:g .\n",
+        );
+        let renamed = parse(
+            "_:z <urn:ex:p> _:x _:w .\n_:x <urn:ex:p> _:y _:w .\n_:y <urn:ex:p> _:z _:w .\n",
+        );
+        // same quad and blank node counts, but a different shape
+        let reversed = parse(
+            "_:a <urn:ex:p> _:b _:g .\n_:b <urn:ex:p> _:a _:g .\n_:c <urn:ex:p> _:a _:g .\n",
+        );
+        let fewer_blank_nodes = parse(
+            "_:a <urn:ex:p> _:b _:g .\n_:b <urn:ex:p> _:a _:g .\n_:a <urn:ex:p> _:a _:g .\n",
+        );
+
+        assert!(is_isomorphic(&cycle, &renamed).unwrap());
+        assert!(!is_isomorphic(&cycle, &reversed).unwrap());
+        assert!(!is_isomorphic(&cycle, &fewer_blank_nodes).unwrap());
+        assert!(!is_isomorphic(&cycle, &Dataset::new()).unwrap());
+
+        // quads without blank nodes must match exactly
+        let with_ground = parse("_:a <urn:ex:p> _:b .\n<urn:ex:s> <urn:ex:p> <urn:ex:o1> .\n");
+        let other_ground = parse("_:a <urn:ex:p> _:b .\n<urn:ex:s> <urn:ex:p> <urn:ex:o2> .\n");
+        assert!(is_isomorphic(&with_ground, &with_ground.clone()).unwrap());
+        assert!(!is_isomorphic(&with_ground, &other_ground).unwrap());
+    }
+
+    #[test]
+    fn isomorphic_datasets_ignore_labeling_options() {
+        use crate::{is_isomorphic_with, CanonicalizationOptions, PrefixCollisionPolicy};
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+        use std::sync::Arc;
+
+        let parse = |input: &str| {
+            Dataset::from_iter(
+                NQuadsParser::new()
+                    .for_slice(input.as_bytes())
+                    .map(|x| x.unwrap()),
+            )
+        };
+        // the identifiers of the first dataset collide with the canonical prefix
+        let colliding = parse("_:c14n0 <urn:ex:p> _:c14n1 .\n");
+        let renamed = parse("_:a <urn:ex:p> _:b .\n");
+
+        for prefix_collision in [
+            PrefixCollisionPolicy::Allow,
+            PrefixCollisionPolicy::Error,
+            PrefixCollisionPolicy::FreshPrefix,
+        ] {
+            let options = CanonicalizationOptions {
+                prefix_collision,
+                ..Default::default()
+            };
+            assert!(
+                is_isomorphic_with::<Sha256>(&colliding, &renamed, &options).unwrap(),
+                "{:?}",
+                prefix_collision
+            );
+        }
+        let options = CanonicalizationOptions {
+            canonical_prefix: Some("doc1_c14n".to_string()),
+            label_formatter: Some(Arc::new(|n| format!("b{}", n))),
+            ..Default::default()
+        };
+        assert!(is_isomorphic_with::<Sha256>(&colliding, &renamed, &options).unwrap());
+    }
+
+    #[test]
+    fn canonical_prefix() {
+        use crate::{canonicalize_with, issue_with, CanonicalizationError, CanonicalizationOptions};
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+
+        let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
+_:e1 <http://example.org/vocab#next> _:e2 _:g .
+_:e2 <http://example.org/vocab#next> _:e0 _:g .
+_:e2 <http://example.org/vocab#label> "two" _:g .
+"#;
+        let input_dataset: Dataset = NQuadsParser::new()
+            .for_slice(input.as_bytes())
+            .map(|x| x.unwrap())
+            .collect();
+        let default_options = CanonicalizationOptions::default();
+        let prefixed_options = CanonicalizationOptions {
+            canonical_prefix: Some("doc1_c14n".to_string()),
+            ..Default::default()
+        };
+
+        // the same blank nodes get the same counters, only the labels differ
+        let default_map = issue_with::<Sha256>(&input_dataset, &default_options).unwrap();
+        let prefixed_map = issue_with::<Sha256>(&input_dataset, &prefixed_options).unwrap();
+        assert_eq!(default_map.len(), prefixed_map.len());
+        for (k, v) in &default_map {
+            assert_eq!(prefixed_map[k], v.replace("c14n", "doc1_c14n"));
+        }
+        let default_form = canonicalize_with::<Sha256>(&input_dataset, &default_options).unwrap();
+        let prefixed_form = canonicalize_with::<Sha256>(&input_dataset, &prefixed_options).unwrap();
+        assert_ne!(default_form, prefixed_form);
+        assert!(prefixed_form.contains("_:doc1_c14n0"));
+
+        let invalid_options = CanonicalizationOptions {
+            canonical_prefix: Some("doc 1".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            issue_with::<Sha256>(&input_dataset, &invalid_options),
+            Err(CanonicalizationError::InvalidCanonicalLabel(label)) if label == "doc 10"
+        ));
+    }
+
+    #[test]
+    fn digest_is_threaded_through() {
+        use crate::{canonicalize_with, CanonicalizationOptions};
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::{Sha256, Sha384};
+        use std::io::Cursor;
+
+        let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
+_:e0 <http://example.org/vocab#prev> _:e2 _:g .
+_:e1 <http://example.org/vocab#next> _:e2 _:g .
+_:e1 <http://example.org/vocab#prev> _:e0 _:g .
+_:e2 <http://example.org/vocab#next> _:e0 _:g .
+_:e2 <http://example.org/vocab#prev> _:e1 _:g .
+"#;
+        let input_quads = NQuadsParser::new()
+            .for_reader(Cursor::new(input))
+            .map(|x| x.unwrap());
+        let input_dataset = Dataset::from_iter(input_quads);
+        let options = CanonicalizationOptions::default();
+
+        // the selected digest decides the labeling, so the outputs differ
+        assert_ne!(
+            canonicalize_with::<Sha256>(&input_dataset, &options).unwrap(),
+            canonicalize_with::<Sha384>(&input_dataset, &options).unwrap()
+        );
+    }
+
+    #[test]
+    fn sort_is_independent_of_input_order() {
+        use crate::{relabel_quads, serialize, sort};
+        use oxrdf::{Dataset, Quad};
+        use oxttl::NQuadsParser;
+        use std::io::Cursor;
+
+        let input = r#"_:e0 <http://example.org/vocab#p> "a" .
+_:e1 <http://example.org/vocab#p> "a" .
+<urn:ex:s> <urn:ex:p> _:e0 .
+<urn:ex:s> <urn:ex:p> _:e1 .
+"#;
+        // both blank nodes share a canonical label, so relabeling yields duplicates
+        let issued_identifiers_map = HashMap::from([
+            ("e0".to_string(), "c14n0".to_string()),
+            ("e1".to_string(), "c14n0".to_string()),
+        ]);
+        let input_quads: Vec<Quad> = NQuadsParser::new()
+            .for_reader(Cursor::new(input))
+            .map(|x| x.unwrap())
+            .collect();
+        let relabeled = relabel_quads(&input_quads, &issued_identifiers_map).unwrap();
+        assert_eq!(relabeled[0], relabeled[1]);
+
+        let expected = "<urn:ex:s> <urn:ex:p> _:c14n0 .\n_:c14n0 <http://example.org/vocab#p> \"a\" .\n";
+        let mut reversed = relabeled.clone();
+        reversed.reverse();
+        for quads in [relabeled, reversed] {
+            let dataset = Dataset::from_iter(quads);
+            assert_eq!(serialize(&dataset), expected);
+            let sorted: String = sort(&dataset)
+                .iter()
+                .map(|q| q.to_string() + " .\n")
+                .collect();
+            assert_eq!(sorted, expected);
+        }
+    }
+
+    #[test]
+    fn prefix_collision() {
+        use crate::{issue_with, CanonicalizationOptions, PrefixCollisionPolicy};
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+        use std::io::Cursor;
+
+        let input = r#"_:c14n0 <http://example.org/vocab#next> _:e0 .
+_:e0 <http://example.org/vocab#next> _:c14n0x .
+"#;
+        let input_quads = NQuadsParser::new()
+            .for_reader(Cursor::new(input))
+            .map(|x| x.unwrap());
+        let input_dataset = Dataset::from_iter(input_quads);
+        let issue = |prefix_collision| {
+            let options = CanonicalizationOptions {
+                prefix_collision,
+                ..Default::default()
+            };
+            issue_with::<Sha256>(&input_dataset, &options)
+        };
+
+        let allowed = issue(PrefixCollisionPolicy::Allow).unwrap();
+        assert!(allowed.values().all(|v| v.starts_with("c14n")));
+
+        assert!(matches!(
+            issue(PrefixCollisionPolicy::Error),
+            Err(CanonicalizationError::PrefixCollision(id)) if id == "c14n0"
+        ));
+
+        let fresh = issue(PrefixCollisionPolicy::FreshPrefix).unwrap();
+        assert_eq!(fresh.len(), allowed.len());
+        for (k, v) in allowed {
+            assert_eq!(fresh[&k], v.replacen("c14n", "c14n_", 1));
+        }
+    }
+
+    #[test]
+    fn normalize_language_tags() {
+        use crate::{canonicalize_with, CanonicalizationOptions};
+        use oxrdf::{BlankNode, Dataset, GraphName, Literal, NamedNode, Quad};
+        use sha2::Sha256;
+
+        // oxrdf lowercases language tags only when they are checked
+        let dataset = |language: &str| {
+            let b = BlankNode::new("b").unwrap();
+            let p = NamedNode::new("http://example.org/vocab#label").unwrap();
+            Dataset::from_iter([
+                Quad::new(
+                    b.clone(),
+                    p.clone(),
+                    Literal::new_language_tagged_literal_unchecked("color", language),
+                    GraphName::DefaultGraph,
+                ),
+                Quad::new(
+                    b,
+                    p,
+                    Literal::new_language_tagged_literal_unchecked("colour", "en-GB"),
+                    GraphName::DefaultGraph,
+                ),
+            ])
+        };
+        let canonicalize = |input_dataset: &Dataset, normalize_language_tags| {
+            let options = CanonicalizationOptions {
+                normalize_language_tags,
+                ..Default::default()
+            };
+            canonicalize_with::<Sha256>(input_dataset, &options).unwrap()
+        };
+
+        let upper = dataset("en-US");
+        let lower = dataset("en-us");
+        assert_ne!(canonicalize(&upper, false), canonicalize(&lower, false));
+        assert_eq!(canonicalize(&upper, true), canonicalize(&lower, true));
+        assert_eq!(
+            canonicalize(&upper, true),
+            r#"_:c14n0 <http://example.org/vocab#label> "color"@en-us .
+_:c14n0 <http://example.org/vocab#label> "colour"@en-gb .
+"#
+        );
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn unicode_normalization() {
+        use crate::{canonicalize_with, CanonicalizationOptions, NormForm};
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+
+        let composed = "_:b <http://example.org/vocab#label> \"caf\u{e9}\" .\n";
+        let decomposed = "_:b <http://example.org/vocab#label> \"cafe\u{301}\" .\n";
+        let canonicalize = |input: &str, unicode_normalization| {
+            let input_quads = NQuadsParser::new()
+                .for_slice(input.as_bytes())
+                .map(|x| x.unwrap());
+            let options = CanonicalizationOptions {
+                unicode_normalization,
+                ..Default::default()
+            };
+            canonicalize_with::<Sha256>(&Dataset::from_iter(input_quads), &options).unwrap()
+        };
+
+        assert_ne!(canonicalize(composed, None), canonicalize(decomposed, None));
+        for form in [NormForm::Nfc, NormForm::Nfd] {
+            assert_eq!(
+                canonicalize(composed, Some(form)),
+                canonicalize(decomposed, Some(form))
+            );
+        }
+        assert_eq!(
+            canonicalize(decomposed, Some(NormForm::Nfc)),
+            canonicalize(composed, None)
+        );
+    }
+
+    #[test]
+    fn label_formatter() {
+        use crate::{canonicalize_with, issue_with, CanonicalizationOptions};
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+        use std::{io::Cursor, sync::Arc};
+
+        let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+_:e1 <http://example.org/vocab#next> _:e0 .
+"#;
+        let input_quads = NQuadsParser::new()
+            .for_reader(Cursor::new(input))
+            .map(|x| x.unwrap());
+        let input_dataset = Dataset::from_iter(input_quads);
+        let options =
+            |label_formatter: Arc<dyn Fn(usize) -> String + Send + Sync>| CanonicalizationOptions {
+                label_formatter: Some(label_formatter),
+                ..Default::default()
+            };
+
+        // the default labels are reproduced by the equivalent formatter
+        assert_eq!(
+            issue_with::<Sha256>(&input_dataset, &options(Arc::new(|n| format!("c14n{}", n))))
+                .unwrap(),
+            issue_with::<Sha256>(&input_dataset, &CanonicalizationOptions::default()).unwrap()
+        );
+
+        let padded = options(Arc::new(|n| format!("b{:04}", n)));
+        assert_eq!(
+            canonicalize_with::<Sha256>(&input_dataset, &padded).unwrap(),
+            r#"_:b0000 <http://example.org/vocab#next> _:b0001 .
+_:b0001 <http://example.org/vocab#next> _:b0000 .
+"#
+        );
+
+        assert!(matches!(
+            issue_with::<Sha256>(&input_dataset, &options(Arc::new(|_| "b".to_string()))),
+            Err(CanonicalizationError::InvalidCanonicalLabel(label)) if label == "b"
+        ));
+        assert!(matches!(
+            issue_with::<Sha256>(&input_dataset, &options(Arc::new(|n| format!("b {}", n)))),
+            Err(CanonicalizationError::InvalidCanonicalLabel(_))
+        ));
+    }
+
+    #[test]
+    fn parse_error() {
+        use oxttl::NQuadsParser;
+        use std::error::Error;
+
+        let input = r#"<urn:ex:s> <urn:ex:p> <urn:ex:o> .
+<urn:ex:s> <urn:ex:p> "unterminated .
+"#;
+        let parse_error = NQuadsParser::new()
+            .for_slice(input.as_bytes())
+            .find_map(Result::err)
+            .unwrap();
+        let message = parse_error.message().to_string();
+        let error = CanonicalizationError::from(parse_error);
+
+        assert!(matches!(error, CanonicalizationError::Parse { line: 2, .. }));
+        assert!(error.to_string().contains(&message));
+        assert!(error.to_string().contains("line 2"));
+        assert!(error.source().is_some());
+    }
+
+    #[test]
+    fn parse_error_from_reader() {
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use std::io::Cursor;
+
+        let input = r#"<urn:ex:s> <urn:ex:p> <urn:ex:o> .
+_:e0 <urn:ex:p> _:e1 .
+<urn:ex:s> <urn:ex:p> <urn:ex:o
+"#;
+        // errors from a reader are `TurtleParseError`s, which also convert into `Parse`
+        let error = NQuadsParser::new()
+            .for_reader(Cursor::new(input))
+            .collect::<Result<Dataset, _>>()
+            .map_err(CanonicalizationError::from)
+            .unwrap_err();
+
+        let CanonicalizationError::Parse { line, column, .. } = &error else {
+            panic!("unexpected error: {:?}", error);
+        };
+        assert_eq!(*line, 3);
+        assert!(*column >= 1);
+        assert!(error
+            .to_string()
+            .starts_with(&format!("Parsing the input failed at line 3, column {}:", column)));
+    }
+
+    #[test]
+    fn count_automorphisms() {
+        use crate::automorphism_count;
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+        use std::io::Cursor;
+
+        let count = |input: &str| {
+            let input_quads = NQuadsParser::new()
+                .for_reader(Cursor::new(input))
+                .map(|x| x.unwrap());
+            automorphism_count::<Sha256>(&Dataset::from_iter(input_quads)).unwrap()
+        };
+
+        // no blank nodes
+        assert_eq!(count("<urn:ex:s> <urn:ex:p> <urn:ex:o> .\n"), 1);
+        // interchangeable leaves
+        assert_eq!(
+            count(
+                r#"<urn:ex:s> <urn:ex:p> _:a .
+<urn:ex:s> <urn:ex:p> _:b .
+<urn:ex:s> <urn:ex:p> _:c .
+"#
+            ),
+            6
+        );
+        // a cycle with next/prev links inside a blank node graph
+        assert_eq!(
+            count(
+                r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
+_:e0 <http://example.org/vocab#prev> _:e2 _:g .
+_:e1 <http://example.org/vocab#next> _:e2 _:g .
+_:e1 <http://example.org/vocab#prev> _:e0 _:g .
+_:e2 <http://example.org/vocab#next> _:e0 _:g .
+_:e2 <http://example.org/vocab#prev> _:e1 _:g .
+"#
+            ),
+            3
+        );
+        // two disjoint undirected edges: swap within each edge and swap the edges
+        assert_eq!(
+            count(
+                r#"_:a <urn:ex:p> _:b .
+_:b <urn:ex:p> _:a .
+_:c <urn:ex:p> _:d .
+_:d <urn:ex:p> _:c .
+"#
+            ),
+            8
+        );
+    }
+
+    #[test]
+    fn progress_callback() {
+        use crate::{issue_with, CanonicalizationOptions};
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+        use std::{
+            io::Cursor,
+            sync::{Arc, Mutex},
+        };
+
+        let issue = |input: &str| {
+            let input_quads = NQuadsParser::new()
+                .for_reader(Cursor::new(input))
+                .map(|x| x.unwrap());
+            let calls = Arc::new(Mutex::new(Vec::new()));
+            let recorded = Arc::clone(&calls);
+            let options = CanonicalizationOptions {
+                progress: Some(Arc::new(move |done, total| {
+                    recorded.lock().unwrap().push((done, total))
+                })),
+                ..Default::default()
+            };
+            issue_with::<Sha256>(&Dataset::from_iter(input_quads), &options).unwrap();
+            let calls = calls.lock().unwrap().clone();
+            calls
+        };
+
+        // a 3-cycle and a 2-cycle make two buckets of shared first degree hashes
+        let calls = issue(
+            r#"_:a0 <urn:ex:next> _:a1 .
+_:a1 <urn:ex:next> _:a2 .
+_:a2 <urn:ex:next> _:a0 .
+_:b0 <urn:ex:link> _:b1 .
+_:b1 <urn:ex:link> _:b0 .
+_:u <urn:ex:label> "unique" .
+"#,
+        );
+        assert_eq!(calls, vec![(1, 2), (2, 2)]);
+
+        // no shared first degree hashes
+        assert!(issue("_:u <urn:ex:label> \"unique\" .\n").is_empty());
+    }
+
+    #[test]
+    fn progress_callback_in_every_issue_function() {
+        use crate::{
+            issue_ordered_with, issue_with_chosen_paths, issue_with_issuer, CanonicalizationOptions,
+        };
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        // a 2-cycle makes one bucket of shared first degree hashes
+        let input_dataset = Dataset::from_iter(
+            NQuadsParser::new()
+                .for_slice("_:b0 <urn:ex:link> _:b1 .\n_:b1 <urn:ex:link> _:b0 .\n".as_bytes())
+                .map(|x| x.unwrap()),
+        );
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&calls);
+        let options = CanonicalizationOptions {
+            progress: Some(Arc::new(move |_, _| {
+                counted.fetch_add(1, Ordering::SeqCst);
+            })),
+            ..Default::default()
+        };
+
+        issue_with_issuer::<Sha256>(&input_dataset, &options).unwrap();
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 1);
+        issue_ordered_with::<Sha256>(&input_dataset, &options).unwrap();
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 1);
+        issue_with_chosen_paths::<Sha256>(&input_dataset, &options).unwrap();
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 1);
+        #[cfg(feature = "transcript")]
+        {
+            let mut transcript = Vec::new();
+            crate::issue_with_transcript::<Sha256>(&input_dataset, &options, Some(&mut transcript))
+                .unwrap();
+            assert_eq!(calls.swap(0, Ordering::SeqCst), 1);
+            assert!(!transcript.is_empty());
+        }
+    }
+
+    #[test]
+    fn issue_ordered_agrees_with_issue() {
+        use crate::{issue, issue_ordered};
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use std::collections::HashMap;
+
+        for entry in std::fs::read_dir("tests/rdfc10").unwrap() {
+            let path = entry.unwrap().path();
+            if !path.to_string_lossy().ends_with("-in.nq") {
+                continue;
+            }
+            let input = std::fs::read(&path).unwrap();
+            let Ok(input_dataset) = NQuadsParser::new()
+                .for_slice(&input)
+                .collect::<Result<Dataset, _>>()
+            else {
+                continue;
+            };
+            let Ok(ordered) = issue_ordered(&input_dataset) else {
+                continue;
+            };
+
+            // the identifiers are listed in the order they were issued
+            for (i, canonical_identifier) in ordered.values().enumerate() {
+                assert_eq!(canonical_identifier, &format!("c14n{}", i), "{:?}", path);
+            }
+            assert_eq!(
+                ordered.into_iter().collect::<HashMap<_, _>>(),
+                issue(&input_dataset).unwrap(),
+                "{:?}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn relabel_quads_keeps_positions() {
+        use crate::{issue, relabel_quads};
+        use oxrdf::{Dataset, Quad};
+        use oxttl::NQuadsParser;
+        use std::io::Cursor;
+
+        let input = r#"_:e0 <http://example.org/vocab#next> _:e1 .
+_:e1 <http://example.org/vocab#next> _:e0 .
+_:e1 <http://example.org/vocab#label> "one" .
+"#;
+        let input_dataset: Dataset = NQuadsParser::new()
+            .for_reader(Cursor::new(input))
+            .map(|x| x.unwrap())
+            .collect();
+        let issued_identifiers_map = issue(&input_dataset).unwrap();
+
+        // an order given by the caller, unrelated to that of the dataset, with a duplicate
+        let mut input_quads: Vec<Quad> = input_dataset.iter().map(Quad::from).collect();
+        input_quads.sort_by_key(|q| std::cmp::Reverse(q.to_string()));
+        input_quads.push(input_quads[0].clone());
+
+        let labeled_quads = relabel_quads(&input_quads, &issued_identifiers_map).unwrap();
+        assert_eq!(labeled_quads.len(), input_quads.len());
+        for (input_quad, labeled_quad) in input_quads.iter().zip(&labeled_quads) {
+            assert_eq!(input_quad.predicate, labeled_quad.predicate);
+            let relabeled = match &input_quad.subject {
+                oxrdf::Subject::BlankNode(b) => issued_identifiers_map[b.as_str()].clone(),
+                _ => unreachable!(),
+            };
+            assert_eq!(labeled_quad.subject.to_string(), format!("_:{}", relabeled));
+        }
+        assert_eq!(labeled_quads.first(), labeled_quads.last());
+    }
+
+    #[test]
+    fn max_quads_per_blank_node() {
+        use crate::{canonicalize_with, issue_with, CanonicalizationError, CanonicalizationOptions};
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+        use std::io::Cursor;
+
+        let input = r#"_:hub <urn:ex:p> "1" .
+_:hub <urn:ex:p> "2" .
+_:hub <urn:ex:p> _:hub .
+_:other <urn:ex:p> "1" .
+_:other <urn:ex:p> _:hub _:other .
+"#;
+        let input_dataset: Dataset = NQuadsParser::new()
+            .for_reader(Cursor::new(input))
+            .map(|x| x.unwrap())
+            .collect();
+        let options = |limit| CanonicalizationOptions {
+            max_quads_per_blank_node: limit,
+            ..Default::default()
+        };
+
+        // _:hub appears twice in one quad, which counts twice as in the blank node to quads map
+        assert!(canonicalize_with::<Sha256>(&input_dataset, &options(Some(5))).is_ok());
+        assert!(matches!(
+            canonicalize_with::<Sha256>(&input_dataset, &options(Some(4))),
+            Err(CanonicalizationError::BlankNodeTooConnected { node, count: 5 }) if node == "hub"
+        ));
+        // _:other exceeds the limit as well, counting the quad where it is the graph name,
+        // but the first blank node in code point order is reported
+        assert!(matches!(
+            issue_with::<Sha256>(&input_dataset, &options(Some(2))),
+            Err(CanonicalizationError::BlankNodeTooConnected { node, count: 5 }) if node == "hub"
+        ));
+        assert!(issue_with::<Sha256>(&input_dataset, &options(None)).is_ok());
+    }
+
+    #[test]
+    fn base16_error_source() {
+        use crate::CanonicalizationError;
+        use std::error::Error;
+
+        let error = CanonicalizationError::from(base16ct::Error::InvalidLength);
+        assert_eq!(
+            error.to_string(),
+            "Base16 encoding failed: invalid Base16 length"
+        );
+        let source = error.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<base16ct::Error>(),
+            Some(&base16ct::Error::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn canonicalize_graphs_ignores_other_graphs() {
+        use crate::{canonicalize_graphs, canonicalize_with, CanonicalizationOptions};
+        use oxrdf::{Dataset, GraphNameRef, NamedNodeRef};
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+        use std::io::Cursor;
+
+        let parse = |input: &str| -> Dataset {
+            NQuadsParser::new()
+                .for_reader(Cursor::new(input.to_string()))
+                .map(|x| x.unwrap())
+                .collect()
+        };
+        let included = r#"_:e0 <http://example.org/vocab#next> _:e1 <urn:ex:g1> .
+_:e1 <http://example.org/vocab#next> _:e2 <urn:ex:g1> .
+"#;
+        // _:e0 and _:e2 look alike within <urn:ex:g1>, but _:e2 is distinguished in <urn:ex:g2>
+        let excluded = r#"_:e2 <http://example.org/vocab#label> "last" <urn:ex:g2> .
+_:e0 <http://example.org/vocab#next> _:e2 <urn:ex:g2> .
+"#;
+        let options = CanonicalizationOptions::default();
+        let g1 = [NamedNodeRef::new("urn:ex:g1").unwrap().into()];
+
+        let canonicalized = canonicalize_graphs::<Sha256>(
+            &parse(&format!("{}{}", included, excluded)),
+            &g1,
+            &options,
+        )
+        .unwrap();
+        // the blank nodes shared with the excluded graph are labeled as if it did not exist
+        assert_eq!(
+            canonicalized,
+            canonicalize_with::<Sha256>(&parse(included), &options).unwrap()
+        );
+        assert_eq!(
+            canonicalized,
+            canonicalize_graphs::<Sha256>(&parse(included), &g1, &options).unwrap()
+        );
+        assert!(!canonicalized.contains("last"));
+
+        // nothing is left if no graph is selected, and all graphs give the whole dataset
+        let dataset = parse(&format!("{}{}", included, excluded));
+        assert_eq!(
+            canonicalize_graphs::<Sha256>(&dataset, &[], &options).unwrap(),
+            ""
+        );
+        let all = [
+            GraphNameRef::DefaultGraph,
+            NamedNodeRef::new("urn:ex:g1").unwrap().into(),
+            NamedNodeRef::new("urn:ex:g2").unwrap().into(),
+        ];
+        assert_eq!(
+            canonicalize_graphs::<Sha256>(&dataset, &all, &options).unwrap(),
+            canonicalize_with::<Sha256>(&dataset, &options).unwrap()
+        );
+    }
+
+    #[cfg(feature = "cffi")]
+    #[test]
+    fn ffi_canonicalize() {
+        use crate::ffi::{
+            rdf_canon_canonicalize, rdf_canon_free, RDF_CANON_ERROR_INVALID_UTF8,
+            RDF_CANON_ERROR_NULL_POINTER, RDF_CANON_ERROR_PARSE, RDF_CANON_OK,
+        };
+        use std::ffi::{c_char, CStr, CString};
+        use std::ptr;
+
+        let canonicalize = |input: &[u8]| {
+            let input = CString::new(input).unwrap();
+            let mut out: *mut c_char = ptr::null_mut();
+            let mut out_len = usize::MAX;
+            let code = unsafe { rdf_canon_canonicalize(input.as_ptr(), &mut out, &mut out_len) };
+            let output = if out.is_null() {
+                None
+            } else {
+                let output = unsafe { CStr::from_ptr(out) }.to_str().unwrap().to_string();
+                unsafe { rdf_canon_free(out) };
+                Some(output)
+            };
+            (code, output, out_len)
+        };
+
+        let input = b"_:e0 <http://example.org/vocab#next> _:e1 .\n\
+_:e1 <http://example.org/vocab#label> \"a\\u0000b\" .\n";
+        let expected = "_:c14n0 <http://example.org/vocab#label> \"a\\u0000b\" .\n\
+_:c14n1 <http://example.org/vocab#next> _:c14n0 .\n";
+        assert_eq!(
+            canonicalize(input),
+            (RDF_CANON_OK, Some(expected.to_string()), expected.len())
+        );
+        assert_eq!(canonicalize(b""), (RDF_CANON_OK, Some(String::new()), 0));
+
+        // no output is left to be freed on error
+        assert_eq!(
+            canonicalize(b"_:e0 <http://example.org/vocab#next> .\n"),
+            (RDF_CANON_ERROR_PARSE, None, 0)
+        );
+        assert_eq!(
+            canonicalize(b"<urn:ex:s> <urn:ex:p> \"\xff\" .\n"),
+            (RDF_CANON_ERROR_INVALID_UTF8, None, 0)
+        );
+
+        let mut out: *mut c_char = ptr::null_mut();
+        let mut out_len = 0;
+        assert_eq!(
+            unsafe { rdf_canon_canonicalize(ptr::null(), &mut out, &mut out_len) },
+            RDF_CANON_ERROR_NULL_POINTER
+        );
+        assert!(out.is_null());
+        let input = CString::new(&input[..]).unwrap();
+        assert_eq!(
+            unsafe { rdf_canon_canonicalize(input.as_ptr(), ptr::null_mut(), &mut out_len) },
+            RDF_CANON_ERROR_NULL_POINTER
+        );
+        unsafe { rdf_canon_free(ptr::null_mut()) };
+    }
+
+    #[test]
+    fn invalid_label_in_issued_identifiers_map() {
+        use crate::{relabel, relabel_quads_lenient, CanonicalizationError};
+        use oxrdf::{Dataset, Quad};
+        use oxttl::NQuadsParser;
+        use std::collections::HashMap;
+        use std::io::Cursor;
+
+        let input = "_:e0 <http://example.org/vocab#next> _:e1 .\n";
+        let input_quads: Vec<Quad> = NQuadsParser::new()
+            .for_reader(Cursor::new(input))
+            .map(|x| x.unwrap())
+            .collect();
+        let input_dataset = Dataset::from_iter(&input_quads);
+        let issued_identifiers_map = HashMap::from([
+            ("e0".to_string(), "c14n0".to_string()),
+            ("e1".to_string(), "not a label".to_string()),
+        ]);
+
+        let error = relabel(&input_dataset, &issued_identifiers_map).unwrap_err();
+        assert!(
+            matches!(&error, CanonicalizationError::BlankNodeIdParseError(id) if id == "not a label")
+        );
+        assert!(error.to_string().contains("not a label"));
+        assert!(matches!(
+            relabel_quads_lenient(&input_quads, &issued_identifiers_map),
+            Err(CanonicalizationError::BlankNodeIdParseError(id)) if id == "not a label"
+        ));
+    }
+
+    #[test]
+    fn relabel_with_ordered_map() {
+        use crate::{issue, issue_ordered, relabel, relabel_quads, relabel_quads_lenient};
+        use std::collections::HashMap;
+        use oxrdf::{Dataset, Quad};
+        use oxttl::NQuadsParser;
+        use std::io::Cursor;
+
+        let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
+_:e1 <http://example.org/vocab#next> _:e2 _:g .
+_:e2 <http://example.org/vocab#next> _:e0 _:g .
+_:e2 <http://example.org/vocab#label> "last" _:g .
+"#;
+        let input_quads: Vec<Quad> = NQuadsParser::new()
+            .for_reader(Cursor::new(input))
+            .map(|x| x.unwrap())
+            .collect();
+        let input_dataset = Dataset::from_iter(&input_quads);
+
+        let ordered = issue_ordered(&input_dataset).unwrap();
+        let issued_identifiers_map = issue(&input_dataset).unwrap();
+        assert_eq!(
+            relabel(&input_dataset, &ordered).unwrap(),
+            relabel(&input_dataset, &issued_identifiers_map).unwrap()
+        );
+        assert_eq!(
+            relabel_quads(&input_quads, &ordered).unwrap(),
+            relabel_quads(&input_quads, &issued_identifiers_map).unwrap()
+        );
+
+        // relabeling borrows the map, which keeps its issuance order
+        let issuance_order: Vec<&str> = ordered.values().map(String::as_str).collect();
+        assert_eq!(issuance_order, vec!["c14n0", "c14n1", "c14n2", "c14n3"]);
+        let equivalent: HashMap<String, String> = ordered.clone().into_iter().collect();
+        assert_eq!(
+            relabel(&input_dataset, &ordered).unwrap(),
+            relabel(&input_dataset, &equivalent).unwrap()
+        );
+        assert_eq!(
+            relabel_quads_lenient(&input_quads, &ordered).unwrap(),
+            relabel_quads_lenient(&input_quads, &equivalent).unwrap()
+        );
+    }
+
+    #[test]
+    fn recanonicalize_with_additions() {
+        use crate::{canonicalize, issue, recanonicalize, relabel, serialize};
+        use oxrdf::{Dataset, Quad};
+        use oxttl::NQuadsParser;
+        use std::io::Cursor;
+
+        let parse = |input: &str| -> Vec<Quad> {
+            NQuadsParser::new()
+                .for_reader(Cursor::new(input))
+                .map(|x| x.unwrap())
+                .collect()
+        };
+        let input = Dataset::from_iter(parse(
+            r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
+_:e0 <http://example.org/vocab#prev> _:e2 _:g .
+_:e1 <http://example.org/vocab#next> _:e2 _:g .
+_:e1 <http://example.org/vocab#prev> _:e0 _:g .
+_:e2 <http://example.org/vocab#next> _:e0 _:g .
+_:e2 <http://example.org/vocab#prev> _:e1 _:g .
+"#,
+        ));
+        let previous = relabel(&input, &issue(&input).unwrap()).unwrap();
+        let from_scratch = |additions: &[Quad]| {
+            let mut extended = previous.clone();
+            extended.extend(additions);
+            canonicalize(&extended).unwrap()
+        };
+
+        // ground additions reuse the previous canonical identifiers
+        let ground = parse(
+            r#"<urn:ex:s> <urn:ex:p> <urn:ex:o> <urn:ex:g> .
+<urn:ex:s> <urn:ex:p> "x" .
+"#,
+        );
+        let (extended, issued_identifiers_map) = recanonicalize(&previous, &ground).unwrap();
+        assert_eq!(serialize(&extended), from_scratch(&ground));
+        assert_eq!(issued_identifiers_map.len(), 4);
+        assert!(issued_identifiers_map.iter().all(|(k, v)| k == v));
+
+        // additions mentioning an existing blank node break the symmetry
+        let touching = parse(
+            r#"_:c14n1 <http://example.org/vocab#label> "first" _:c14n0 .
+"#,
+        );
+        let (extended, issued_identifiers_map) = recanonicalize(&previous, &touching).unwrap();
+        assert_eq!(serialize(&extended), from_scratch(&touching));
+        assert_eq!(issued_identifiers_map.len(), 4);
+
+        // additions with new blank nodes
+        let new = parse("_:x <http://example.org/vocab#next> _:c14n2 .\n");
+        let (extended, issued_identifiers_map) = recanonicalize(&previous, &new).unwrap();
+        assert_eq!(serialize(&extended), from_scratch(&new));
+        assert_eq!(issued_identifiers_map.len(), 5);
+    }
+
+    #[test]
+    fn split_connected_components() {
+        use crate::connected_components;
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use std::io::Cursor;
+
+        let input = r#"_:b <urn:ex:p> _:c .
+<urn:ex:s> <urn:ex:p> "ground" .
+_:a <urn:ex:p> _:x _:g .
+_:c <urn:ex:p> "c" .
+_:g <urn:ex:p> _:a .
+_:d <urn:ex:p> <urn:ex:o> .
+"#;
+        let input_quads = NQuadsParser::new()
+            .for_reader(Cursor::new(input))
+            .map(|x| x.unwrap());
+        let input_dataset = Dataset::from_iter(input_quads);
+        let components = connected_components(&input_dataset);
+
+        // ground quads, then {a, g, x}, {b, c}, {d} by their smallest identifiers
+        assert_eq!(
+            components.iter().map(Dataset::len).collect::<Vec<_>>(),
+            vec![1, 2, 2, 1]
+        );
+        assert!(components[0].iter().all(|q| q.to_string().contains("ground")));
+        for component in &components[1..] {
+            assert_eq!(connected_components(component).len(), 1);
+        }
+        let union: Dataset = components.iter().flat_map(|c| c.iter()).collect();
+        assert_eq!(union, input_dataset);
+
+        assert!(connected_components(&Dataset::new()).is_empty());
+    }
+
+    #[test]
+    fn max_hndq_depth() {
+        use crate::issue_with_stats;
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+        use std::io::Cursor;
+
+        let stats = |input: &str| {
+            let input_quads = NQuadsParser::new()
+                .for_reader(Cursor::new(input))
+                .map(|x| x.unwrap());
+            let options = CanonicalizationOptions::default();
+            issue_with_stats::<Sha256>(&Dataset::from_iter(input_quads), &options)
+                .unwrap()
+                .1
+        };
+
+        // unique first-degree hashes never call the Hash N-Degree Quads algorithm
+        let unique = stats(
+            r#"_:a <urn:ex:p> "a" .
+_:b <urn:ex:p> "b" .
+"#,
+        );
+        assert_eq!(unique.hndq_calls, 0);
+        assert_eq!(unique.max_depth, 0);
+
+        // a directed cycle has to recurse into the related blank nodes
+        let cycle = stats(
+            r#"_:e0 <http://example.org/vocab#next> _:e1 .
+_:e1 <http://example.org/vocab#next> _:e2 .
+_:e2 <http://example.org/vocab#next> _:e0 .
+"#,
+        );
+        assert!(cycle.hndq_calls > 0);
+        assert!(cycle.max_depth > 1);
+    }
+
+    #[test]
+    fn hndq_calls_as_call_limit() {
+        use crate::issue_with_stats;
+        use oxrdf::Dataset;
+        use oxttl::NQuadsParser;
+        use sha2::Sha256;
+        use std::io::Cursor;
+
+        let input = r#"_:e0 <http://example.org/vocab#next> _:e1 _:g .
+_:e0 <http://example.org/vocab#prev> _:e2 _:g .
+_:e1 <http://example.org/vocab#next> _:e2 _:g .
+_:e1 <http://example.org/vocab#prev> _:e0 _:g .
+_:e2 <http://example.org/vocab#next> _:e0 _:g .
+_:e2 <http://example.org/vocab#prev> _:e1 _:g .
+"#;
+        let input_quads = NQuadsParser::new()
+            .for_reader(Cursor::new(input))
+            .map(|x| x.unwrap());
+        let input_dataset = Dataset::from_iter(input_quads);
+        let issue = |hndq_call_limit| {
+            let options = CanonicalizationOptions {
+                hndq_call_limit,
+                ..Default::default()
+            };
+            issue_with_stats::<Sha256>(&input_dataset, &options)
+        };
+
+        // the reported number of calls is exactly the smallest limit that succeeds
+        let (issued_identifiers_map, stats) = issue(None).unwrap();
+        assert!(stats.hndq_calls > 0);
+        let (tight, _) = issue(Some(stats.hndq_calls)).unwrap();
+        assert_eq!(tight, issued_identifiers_map);
+        assert!(matches!(
+            issue(Some(stats.hndq_calls - 1)),
+            Err(CanonicalizationError::HndqCallLimitExceeded { limit, .. })
+                if limit == stats.hndq_calls - 1
+        ));
+    }
+
+    #[cfg(feature = "rdf-star")]
+    #[test]
+    fn quoted_triples_in_text_entry_points() {
+        use crate::{
+            canonicalize_cow, canonicalize_from_reader, canonicalize_media_type,
+            verify_canonical_stream,
+        };
+        use sha2::Sha256;
+        use std::io::Cursor;
+
+        // the blank nodes appear only in the quoted triple
+        let input = "<< _:zzz <urn:ex:p> _:yyy >> <urn:ex:q> \"x\" .\n";
+        let canonicalized = canonicalize_cow(input).unwrap().into_owned();
+        assert_ne!(canonicalized, input);
+        assert_eq!(
+            canonicalize_from_reader::<_, Sha256>(Cursor::new(input), &Default::default())
+                .unwrap(),
+            canonicalized
+        );
+        assert_eq!(
+            canonicalize_media_type(input.as_bytes(), "application/n-quads").unwrap(),
+            canonicalized
+        );
+        assert_eq!(
+            canonicalize_media_type(input.as_bytes(), "application/n-triples").unwrap(),
+            canonicalized
+        );
+
+        // blank nodes in quoted triples must carry their canonical identifiers too
+        assert!(verify_canonical_stream(Cursor::new(&canonicalized)).unwrap());
+        assert!(!verify_canonical_stream(Cursor::new(input)).unwrap());
+    }
+
     #[cfg(feature = "earl-reporting")]
     fn setup_earl_reporting() -> (String, impl Fn(String) -> String) {
         const DEVELOPER_ID: &str = "https://github.com/yamdan";
@@ -0,0 +1,25 @@
+/// The hashing operation of the canonicalization algorithm that produced a
+/// transcript entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptOperation {
+    /// **4.6 Hash First Degree Quads**
+    HashFirstDegreeQuads,
+    /// **4.7 Hash Related Blank Node**
+    HashRelatedBlankNode,
+    /// **4.8 Hash N-Degree Quads**
+    HashNDegreeQuads,
+}
+
+/// A single invocation of the hash algorithm during canonicalization.
+///
+/// Entries are recorded in the order in which the hashes are computed,
+/// so that a verifier can replay the canonicalization deterministically by
+/// hashing each `input` and comparing the result with `output`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptEntry {
+    pub operation: TranscriptOperation,
+    /// The exact bytes passed to the hash algorithm.
+    pub input: Vec<u8>,
+    /// The lowercase, hexadecimal representation of the resulting digest.
+    pub output: String,
+}